@@ -0,0 +1,70 @@
+use b3::{bit_cursor::BitCursor, byte_order::NetworkOrder};
+
+use crate::{error::PacketRsResult, packetrs_read::PacketrsRead, read_options::ReadOptions};
+
+/// The order in which the bits of a bitmap field are read, via `#[packetrs(bit_order = "...")]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    /// "msb" (the default): the bits are in the order they're read, so the first is the most
+    /// significant bit of the first byte
+    Msb,
+    /// "lsb": the first bit is the least significant bit of the first byte, i.e. the bits of each
+    /// byte are reversed.  Whole bytes are read: if the number of bits isn't a multiple of 8, the
+    /// last bits are the least significant bits of the final byte, and its remaining (most
+    /// significant) bits are padding, which is skipped.
+    Lsb,
+}
+
+/// Read the given number of bits in the given order (so for Lsb, the bits are read a byte at a time,
+/// including any padding).  Called by generated code, which collects them into the field's type
+/// (e.g. a BitVec or a Vec<bool>).
+#[doc(hidden)]
+pub fn read_bits(
+    buf: &mut BitCursor,
+    num_bits: usize,
+    bit_order: BitOrder,
+    read_options: &mut ReadOptions,
+) -> PacketRsResult<Vec<bool>> {
+    read_options.check_collection_len(num_bits)?;
+    read_options.allocate(num_bits)?;
+    let mut bits = Vec::with_capacity(num_bits);
+    match bit_order {
+        BitOrder::Msb => {
+            for _ in 0..num_bits {
+                bits.push(bool::read::<NetworkOrder>(buf, ())?);
+            }
+        }
+        BitOrder::Lsb => {
+            for byte_start in (0..num_bits).step_by(8) {
+                let mut byte = [false; 8];
+                for bit in byte.iter_mut() {
+                    *bit = bool::read::<NetworkOrder>(buf, ())?;
+                }
+                let num_byte_bits = (num_bits - byte_start).min(8);
+                bits.extend(byte.iter().rev().take(num_byte_bits));
+            }
+        }
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_bits() {
+        let mut buf = BitCursor::from_vec(vec![0b1000_0001, 0b0100_0000]);
+        let bits = read_bits(&mut buf, 10, BitOrder::Msb, &mut ReadOptions::default()).unwrap();
+        let set: Vec<usize> = (0..bits.len()).filter(|&i| bits[i]).collect();
+        assert_eq!(set, vec![0, 7, 9]);
+
+        // The last 2 bits are the least significant bits of the second byte, whose other bits are
+        // skipped
+        let mut buf = BitCursor::from_vec(vec![0b1000_0010, 0b1100_0010]);
+        let bits = read_bits(&mut buf, 10, BitOrder::Lsb, &mut ReadOptions::default()).unwrap();
+        let set: Vec<usize> = (0..bits.len()).filter(|&i| bits[i]).collect();
+        assert_eq!(set, vec![1, 7, 9]);
+        assert_eq!(buf.bits_remaining(), 0);
+    }
+}
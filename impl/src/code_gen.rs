@@ -2,13 +2,16 @@ use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 
 use crate::{
-    get_param,
+    get_param, has_param,
     model_types::{
         are_fields_named, PacketRsAttributeParam, PacketRsEnum, PacketRsEnumVariant, PacketRsField,
         PacketRsStruct,
     },
     syn_helpers::{
-        get_ctx_type, get_ident_of_inner_type, get_var_name_from_fn_arg, is_collection, is_option,
+        coerce_compared_literals, ctx_lifetime, get_backing_primitive, get_collection_elements,
+        get_ctx_type, get_ctx_types, get_inner_type, get_lifetimes, get_named_ctx_arg,
+        get_var_name_from_fn_arg, get_var_type_from_fn_arg, is_collection, is_option, is_vec,
+        parse_exprs_from_lit_str, CollectionElements,
     },
 };
 
@@ -24,98 +27,420 @@ pub(crate) fn get_crate_name() -> syn::Ident {
     syn::Ident::new(&crate_name, Span::call_site())
 }
 
-/// Based on whether the 'inner' type of the given field (i.e. the type that will actually be read
-/// from the buffer) is 'built-in' or not (from BitCursor's perspective), generate and return the
-/// call to read the value from a buffer.
-fn generate_read_call(field: &PacketRsField, read_context: &Vec<syn::Expr>) -> TokenStream {
-    let inner_type = get_ident_of_inner_type(field.ty)
-        .unwrap_or_else(|| panic!("Unable to get ident of inner type from: {:#?}", &field.ty));
+/// Return tokens for the given type that can be used as the receiver of an associated function
+/// call: plain paths (e.g. `u8`) are used as-is, anything else (e.g. `[u8; 6]`) is wrapped in
+/// angle brackets.
+fn type_as_receiver(ty: &syn::Type) -> TokenStream {
+    match ty {
+        syn::Type::Path(tp) if tp.path.segments.iter().all(|s| s.arguments.is_empty()) => {
+            quote! { #ty }
+        }
+        _ => quote! { <#ty> },
+    }
+}
+
+/// If the given 'ctx' expressions are named arguments (e.g. "length = attr_len"), return the name
+/// and value of each.  Returns None if they're positional (a mix of both is rejected by
+/// validate_param_values).
+fn get_named_ctx_args(read_context: &[syn::Expr]) -> Option<Vec<(&syn::Ident, &syn::Expr)>> {
+    let named_args = read_context
+        .iter()
+        .filter_map(get_named_ctx_arg)
+        .collect::<Vec<(&syn::Ident, &syn::Expr)>>();
+    if named_args.is_empty() {
+        None
+    } else if named_args.len() != read_context.len() {
+        unreachable!(
+            "'ctx' arguments must be either all named or all positional: {}",
+            quote! { #(#read_context),* }
+        );
+    } else {
+        Some(named_args)
+    }
+}
+
+/// Generate the context argument passed to the read of a value of the given type from the
+/// expressions in a 'ctx' attribute.  Positional arguments are passed as a tuple.  Named
+/// arguments are used to build the type's named context struct (see PacketrsNamedCtx), which is
+/// then converted into the tuple.  The struct is built through a local alias of the 'Named' type,
+/// since a struct expression can't use a qualified path directly.
+fn generate_ctx_arg(ty: &syn::Type, read_context: &[syn::Expr]) -> TokenStream {
+    if let Some(named_args) = get_named_ctx_args(read_context) {
+        let crate_name = get_crate_name();
+        let (names, values): (Vec<&syn::Ident>, Vec<&syn::Expr>) = named_args.into_iter().unzip();
+        quote! {
+            {
+                type PacketrsNamed<'packetrs_named> = <#ty as ::#crate_name::packetrs_read::PacketrsNamedCtx<'packetrs_named>>::Named;
+                <#ty as ::#crate_name::packetrs_read::PacketrsNamedCtx<'_>>::into_ctx(
+                    PacketrsNamed { #(#names: #values),* }
+                )
+            }
+        }
+    } else {
+        quote! { (#(#read_context,)*) }
+    }
+}
+
+/// Generate and return the call to read a value of the given type (i.e. the type that will actually
+/// be read from the buffer, such as the element type of a collection) from a buffer.
+fn generate_read_call(
+    field: &PacketRsField,
+    ty: &syn::Type,
+    read_context: &[syn::Expr],
+) -> TokenStream {
+    // A time format means the value is read as one of the NTP timestamp types and then converted
+    // into the field's type.
+    let (read_type, conversion) = if let Some(time_format) = get_param!(&field.parameters, TimeFormat) {
+        let crate_name = get_crate_name();
+        let read_type = match time_format.value().as_str() {
+            "ntp64" => quote! { ::#crate_name::ntp::NtpTimestamp },
+            "ntp32" => quote! { ::#crate_name::ntp::NtpShortTimestamp },
+            p => unreachable!("Invalid time format param: {}", p),
+        };
+        (read_type, quote! { .map(::core::convert::Into::into) })
+    } else {
+        (type_as_receiver(ty), TokenStream::new())
+    };
+    let ctx_arg = generate_ctx_arg(ty, read_context);
 
     match get_param!(&field.parameters, ByteOrder).map_or("network_order".to_owned(), |f| f.value()).as_str() {
         "big_endian" | "network_order" => {
             quote! {
-                #inner_type::read::<NetworkOrder>(buf, (#(#read_context,)*))
+                #read_type::read_with_options::<NetworkOrder>(buf, #ctx_arg, read_options)#conversion
             }
         },
         "little_endian" => {
             quote! {
-                #inner_type::read::<LittleEndian>(buf, (#(#read_context,)*))
+                #read_type::read_with_options::<LittleEndian>(buf, #ctx_arg, read_options)#conversion
             }
         },
-        p @ _ => panic!("Invalid byte order param: {}", p),
+        p => unreachable!("Invalid byte order param: {}", p),
     }
 }
 
-fn generate_field_read(field: &PacketRsField) -> TokenStream {
+/// Generate the read of an element of the given field: a value of a collection, or the value of an
+/// Option.  If the field has 'inner' params, or the element is itself a collection or an Option,
+/// the element is read like a field with those params (which inherit the field's byte order and
+/// context, unless they have their own).  Without 'inner' params, a string field's elements (e.g.
+/// the value of an Option<String>) are read as strings with the field's 'string' params.
+/// Otherwise the element is read as a plain value of its type.
+fn generate_element_read(
+    field: &PacketRsField,
+    element_ty: &syn::Type,
+    read_context: &[syn::Expr],
+) -> TokenStream {
+    let inner_params = get_param!(&field.parameters, Inner);
+    let is_string = get_param!(&field.parameters, StringEncoding).is_some();
+    if inner_params.is_none() && !is_string && !is_collection(element_ty) && !is_option(element_ty) {
+        return generate_read_call(field, element_ty, read_context);
+    }
+    let mut parameters = inner_params.cloned().unwrap_or_default();
+    if inner_params.is_none() {
+        parameters.extend(field.parameters.iter().filter(|param| {
+            matches!(
+                param,
+                PacketRsAttributeParam::StringEncoding(_)
+                    | PacketRsAttributeParam::ByteLen(_)
+                    | PacketRsAttributeParam::CStr
+                    | PacketRsAttributeParam::LenPrefix(_)
+            )
+        }).cloned());
+    }
+    if get_param!(&parameters, ByteOrder).is_none() {
+        if let Some(byte_order) = get_param!(&field.parameters, ByteOrder) {
+            parameters.push(PacketRsAttributeParam::ByteOrder(byte_order.clone()));
+        }
+    }
+    if get_param!(&parameters, TimeFormat).is_none() {
+        if let Some(time_format) = get_param!(&field.parameters, TimeFormat) {
+            parameters.push(PacketRsAttributeParam::TimeFormat(time_format.clone()));
+        }
+    }
+    if get_param!(&parameters, CallerContext).is_none() {
+        if let Some(read_context) = get_param!(&field.parameters, CallerContext) {
+            parameters.push(PacketRsAttributeParam::CallerContext(read_context.clone()));
+            if let Some(delimiter) = get_param!(&field.parameters, CtxDelim) {
+                parameters.push(PacketRsAttributeParam::CtxDelim(delimiter.clone()));
+            }
+        }
+    }
+    generate_value_read(&PacketRsField {
+        name: field.name.clone(),
+        ty: element_ty,
+        parameters,
+    })
+}
+
+/// Generate the read of a string field (a String, or anything that can be converted from one, like
+/// Cow<str>) with the given encoding.  Its bytes are those given by its 'byte_len', 'cstr' or
+/// 'len_prefix' param.
+fn generate_string_read(field: &PacketRsField, ty: &syn::Type, encoding: &syn::LitStr) -> TokenStream {
+    let crate_name = get_crate_name();
+    if let syn::Type::Reference(_) = ty {
+        unreachable!("String field {:?} is a reference (checked by validate_param_values)", field.name);
+    }
+    let encoding = match encoding.value().as_str() {
+        "utf8" => quote! { Utf8 },
+        "ascii" => quote! { Ascii },
+        "latin1" => quote! { Latin1 },
+        p => unreachable!("Invalid string param: {}", p),
+    };
+    let bytes_read = match (
+        get_param!(&field.parameters, ByteLen),
+        has_param!(&field.parameters, CStr),
+        get_param!(&field.parameters, LenPrefix),
+    ) {
+        (Some(byte_len), false, None) => quote! {
+            let len: u32 = ::core::convert::TryInto::<u32>::try_into(#byte_len)
+                .map_err(|_| ::#crate_name::anyhow::anyhow!("'byte_len' value doesn't fit in a u32"))?;
+            ::#crate_name::string::read_string_bytes(buf, len as usize, read_options)?
+        },
+        (None, true, None) => quote! {
+            ::#crate_name::string::read_cstr_bytes(buf, read_options)?
+        },
+        (None, false, Some(len_prefix)) => {
+            let len_read_call = generate_read_call(field, len_prefix, &[]);
+            quote! {
+                let len: u32 = ::core::convert::TryInto::<u32>::try_into(#len_read_call?)
+                    .map_err(|_| ::#crate_name::anyhow::anyhow!("Length prefix doesn't fit in a u32"))?;
+                ::#crate_name::string::read_string_bytes(buf, len as usize, read_options)?
+            }
+        }
+        _ => panic!(
+            "Field {:?} is a string: exactly one of 'byte_len', 'cstr' or 'len_prefix' is required",
+            field.name
+        ),
+    };
+    quote! {
+        (|| -> ::#crate_name::error::PacketRsResult<#ty> {
+            let bytes = {
+                #bytes_read
+            };
+            let value = ::#crate_name::string::decode_string(bytes, ::#crate_name::string::StringEncoding::#encoding)?;
+            Ok(::core::convert::From::from(value))
+        })()
+    }
+}
+
+/// Generate the read of an integer field with the given variable-length encoding.  The value is
+/// decoded as a u64 (or an i64, for the signed encodings) and then converted into the field's type,
+/// which fails if it doesn't fit.
+fn generate_varint_read(ty: &syn::Type, encoding: &syn::LitStr) -> TokenStream {
+    let crate_name = get_crate_name();
+    let (read_fn, decoded_ty) = match encoding.value().as_str() {
+        "quic_varint" => (quote! { read_quic_varint }, quote! { u64 }),
+        "uleb128" => (quote! { read_uleb128 }, quote! { u64 }),
+        "sleb128" => (quote! { read_sleb128 }, quote! { i64 }),
+        "zigzag" => (quote! { read_zigzag }, quote! { i64 }),
+        p => unreachable!("Invalid encoding param: {}", p),
+    };
+    let error_message = format!("Value {{}} doesn't fit in {}", quote! { #ty });
+    quote! {
+        ::#crate_name::varint::#read_fn(buf).and_then(|value| {
+            <#ty as ::core::convert::TryFrom<#decoded_ty>>::try_from(value)
+                .map_err(|_| ::#crate_name::anyhow::anyhow!(#error_message, value))
+        })
+    }
+}
+
+/// Generate the read of a bitmap field (e.g. a BitVec or Vec<bool>) of the given number of bits, in
+/// the order given by its 'bit_order' param.
+fn generate_bitmap_read(field: &PacketRsField, ty: &syn::Type, bits: &syn::Expr) -> TokenStream {
+    let crate_name = get_crate_name();
+    let bit_order = match get_param!(&field.parameters, BitOrder).map(|o| o.value()) {
+        Some(ref bit_order) if bit_order == "lsb" => quote! { Lsb },
+        _ => quote! { Msb },
+    };
+    quote! {
+        (|| -> ::#crate_name::error::PacketRsResult<#ty> {
+            let num_bits: u32 = ::core::convert::TryInto::<u32>::try_into(#bits)
+                .map_err(|_| ::#crate_name::anyhow::anyhow!("'bits' value doesn't fit in a u32"))?;
+            let bits = ::#crate_name::bitmap::read_bits(buf, num_bits as usize, ::#crate_name::bitmap::BitOrder::#bit_order, read_options)?;
+            Ok(bits.into_iter().collect())
+        })()
+    }
+}
+
+/// Generate the read of the given field's value from the buffer, as an expression which evaluates
+/// to a PacketRsResult of the value.  This is also used for the elements of collections and
+/// Options whose reads have their own params (see 'inner').
+fn generate_value_read(field: &PacketRsField) -> TokenStream {
     let crate_name = get_crate_name();
     let field_name = &field.name;
-    let field_ty = &field.ty;
-    let error_context = field_name
-        .as_ref()
-        .unwrap_or_else(|| panic!("Unable to get name of field for error_context {:#?}", field))
-        .to_string();
+    // If the field is read as a different type, then that type determines how it's read
+    // (e.g. whether it's a collection), and 'map' or 'try_map' converts it to the field's type.
+    let field_ty = get_param!(&field.parameters, ReadAs).unwrap_or(field.ty);
 
-    // Generate the context assignments, if there are any.
-    let read_context = if let Some(read_context) = get_param!(&field.parameters, CallerContext) {
-        let delimiter = get_param!(&field.parameters, CtxDelim)
-            .map(|ls| ls.value())
-            .unwrap_or(",".to_owned());
-        read_context
+    // Generate the context assignments, if there are any.  An explicit delimiter means the legacy
+    // behavior of splitting the value on it before parsing each expression.
+    let read_context = match (
+        get_param!(&field.parameters, CallerContext),
+        get_param!(&field.parameters, CtxDelim),
+    ) {
+        (Some(read_context), Some(delimiter)) => read_context
             .value()
-            .split::<&str>(delimiter.as_ref())
+            .split::<&str>(delimiter.value().as_ref())
             .map(syn::parse_str::<syn::Expr>)
             .collect::<Result<Vec<syn::Expr>, syn::Error>>()
             .unwrap_or_else(|e| {
                 panic!(
                     "Error parsing 'ctx' value as Vec of expressions using delimiter {}: {}, {:?}",
-                    delimiter, e, read_context
+                    delimiter.value(),
+                    e,
+                    read_context
                 )
-            })
-    } else {
-        Vec::new()
+            }),
+        (Some(read_context), None) => parse_exprs_from_lit_str(read_context)
+            .unwrap_or_else(|e| {
+                panic!("Error parsing 'ctx' value as list of expressions: {}, {:?}", e, read_context)
+            }),
+        (None, _) => Vec::new(),
     };
 
-    if let Some(ref read_value) = get_param!(&field.parameters, ReadValue) {
-        return quote! {
-            let #field_name = #read_value;
-        };
-    }
-
-    let read_call = if let Some(ref custom_reader_value) =
+    let default_value = get_param!(&field.parameters, Default);
+    let read_call = if let Some(peek_bits) = get_param!(&field.parameters, PeekBits) {
+        // The bits are read one at a time (most significant first) into the field's type, which
+        // doesn't have to be the same width.  They're collected in a u64, so at most 64 can be
+        // peeked (a literal value is checked by validate_param_values).
+        let error_message = format!("Peeked bits don't fit in {}: {{:#x}}", quote! { #field_ty });
+        quote! {
+            (|| {
+                let num_bits: u32 = ::core::convert::TryInto::<u32>::try_into(#peek_bits)
+                    .map_err(|_| ::#crate_name::anyhow::anyhow!("'peek_bits' value doesn't fit in a u32"))?;
+                if num_bits > 64 {
+                    ::#crate_name::anyhow::bail!("Can't peek {} bits: at most 64 bits can be peeked", num_bits);
+                }
+                let mut bits: u64 = 0;
+                for _ in 0..num_bits {
+                    let bit = <bool as ::#crate_name::packetrs_read::PacketrsRead<()>>::read::<NetworkOrder>(buf, ())?;
+                    bits = (bits << 1) | (bit as u64);
+                }
+                <#field_ty as ::core::convert::TryFrom<u64>>::try_from(bits)
+                    .map_err(|_| ::#crate_name::anyhow::anyhow!(#error_message, bits))
+            })()
+        }
+    } else if let Some(ref custom_reader_value) =
         get_param!(&field.parameters, CustomReader)
     {
+        // A custom reader's 'ctx' arguments are positional (checked by validate_param_values)
         quote! {
             #custom_reader_value(buf, (#(#read_context,)*))
         }
+    } else if let Some(encoding) = get_param!(&field.parameters, Encoding) {
+        generate_varint_read(field_ty, encoding)
+    } else if let Some(bits) = get_param!(&field.parameters, Bits) {
+        generate_bitmap_read(field, field_ty, bits)
     } else {
-        let field_read_call = generate_read_call(field, &read_context);
-        if is_collection(field_ty) {
+        // A 'count' or 'while' param may be inherited from an enum variant by all of its fields, so
+        // it only makes a field a collection if its type has collection elements.
+        let collection_elements = get_collection_elements(field_ty).filter(|_| {
+            is_collection(field_ty)
+                || get_param!(&field.parameters, Count).is_some()
+                || get_param!(&field.parameters, While).is_some()
+        });
+        if let Some(elements) = collection_elements {
+            // The elements are read into a Vec, which is then converted into the field's type.  The
+            // elements of a map are key/value pairs: the key is read first, so the value's 'ctx' can
+            // refer to it as 'key' (the only local, other than 'index' and 'elements' below, that
+            // user expressions can see).
+            let (inner_type, element_read) = match elements {
+                CollectionElements::Sequence(inner_type) => {
+                    let field_read_call = generate_element_read(field, inner_type, &read_context);
+                    (quote! { #inner_type }, quote! { #field_read_call? })
+                }
+                CollectionElements::Map(key_type, value_type) => {
+                    // The key only takes the field's byte order: params like 'time_format' are
+                    // for the value.
+                    let key_field = PacketRsField {
+                        name: field.name.clone(),
+                        ty: key_type,
+                        parameters: get_param!(&field.parameters, ByteOrder)
+                            .map(|byte_order| vec![PacketRsAttributeParam::ByteOrder(byte_order.clone())])
+                            .unwrap_or_default(),
+                    };
+                    let key_read_call = generate_read_call(&key_field, key_type, &[]);
+                    let value_read_call = generate_element_read(field, value_type, &read_context);
+                    let element_read = quote! {
+                        {
+                            let key = #key_read_call?;
+                            let __packetrs_value = #value_read_call?;
+                            (key, __packetrs_value)
+                        }
+                    };
+                    (quote! { (#key_type, #value_type) }, element_read)
+                }
+            };
+            let collected = match elements {
+                CollectionElements::Sequence(_) if is_vec(field_ty) => quote! { Ok(__packetrs_values) },
+                CollectionElements::Sequence(_) => quote! { Ok(__packetrs_values.into_iter().collect()) },
+                CollectionElements::Map(..) => {
+                    let insert = match get_param!(&field.parameters, OnDuplicate).map(|d| d.value()) {
+                        Some(ref on_duplicate) if on_duplicate == "first" => quote! {
+                            __packetrs_map.entry(__packetrs_key).or_insert(__packetrs_value);
+                        },
+                        Some(ref on_duplicate) if on_duplicate == "last" => quote! {
+                            __packetrs_map.insert(__packetrs_key, __packetrs_value);
+                        },
+                        _ => quote! {
+                            if __packetrs_map.insert(__packetrs_key, __packetrs_value).is_some() {
+                                ::#crate_name::anyhow::bail!("Duplicate key in element {}", __packetrs_index);
+                            }
+                        },
+                    };
+                    quote! {
+                        let mut __packetrs_map = <#field_ty as ::core::default::Default>::default();
+                        for (__packetrs_index, (__packetrs_key, __packetrs_value)) in
+                            __packetrs_values.into_iter().enumerate()
+                        {
+                            #insert
+                        }
+                        Ok(__packetrs_map)
+                    }
+                }
+            };
+            // Each element's index, and the elements read before it, are available to the 'ctx' (and
+            // 'while') expressions as 'index' and 'elements'.  These are the only locals that the
+            // expressions can see: the others are prefixed so they don't shadow the type's fields.
+            let element_locals = quote! {
+                #[allow(unused_variables)]
+                let index = __packetrs_values.len();
+                #[allow(unused_variables)]
+                let elements: &[#inner_type] = &__packetrs_values;
+            };
             // Must have a 'count' or 'while' param
             if let Some(ref count_param_value) = get_param!(&field.parameters, Count) {
+                // The count is checked against the limits before anything is read (or allocated)
                 quote! {
-                    (0u32..#count_param_value.into())
-                        .map(|_| #field_read_call)
-                        .map(|r| r.map_err(|e| e.into()))
-                        .collect::<::#crate_name::error::PacketRsResult<#field_ty>>()
+                    (|| -> ::#crate_name::error::PacketRsResult<#field_ty> {
+                        let __packetrs_count: u32 = ::core::convert::TryInto::<u32>::try_into(#count_param_value)
+                            .map_err(|_| ::#crate_name::anyhow::anyhow!("'count' value doesn't fit in a u32"))?;
+                        read_options.check_collection_len(__packetrs_count as usize)?;
+                        read_options.allocate((__packetrs_count as usize).saturating_mul(::core::mem::size_of::<#inner_type>()))?;
+                        let mut __packetrs_values = Vec::<#inner_type>::new();
+                        for _ in 0..__packetrs_count {
+                            #element_locals
+                            let __packetrs_value = #element_read;
+                            __packetrs_values.push(__packetrs_value);
+                        }
+                        #collected
+                    })()
                 }
             } else if let Some(ref while_param_value) = get_param!(&field.parameters, While) {
-                // Note that, unlike the 'count' branch where we can collect directly into
-                // Result<Vec<inner_ty>>, here (because of the while loop) we need to create
-                // a Vec<Result<inner_ty>> and then convert it.
-                // TODO: Is there a way to do that directly with a 'while'-style condition?
-                let inner_type = get_ident_of_inner_type(field_ty).unwrap_or_else(|| {
-                    panic!(
-                        "Unable to get inner type of collection type: {:?}",
-                        field_ty
-                    )
-                });
                 quote! {
-                    (|| {
-                        let mut values = Vec::<::#crate_name::error::PacketRsResult<#inner_type>>::new();
-                        while #while_param_value {
-                            values.push(#field_read_call.map_err(|e| e.into()));
+                    (|| -> ::#crate_name::error::PacketRsResult<#field_ty> {
+                        let mut __packetrs_values = Vec::<#inner_type>::new();
+                        loop {
+                            #element_locals
+                            if !(#while_param_value) {
+                                break;
+                            }
+                            read_options.check_while_iterations(index)?;
+                            read_options.check_collection_len(index + 1)?;
+                            read_options.allocate(::core::mem::size_of::<#inner_type>())?;
+                            let __packetrs_value = #element_read;
+                            __packetrs_values.push(__packetrs_value);
                         }
-                        values.into_iter().collect::<::#crate_name::error::PacketRsResult<#field_ty>>()
+                        #collected
                     })()
                 }
             } else {
@@ -124,30 +449,148 @@ fn generate_field_read(field: &PacketRsField) -> TokenStream {
                     field_name
                 );
             }
-        } else if is_option(field_ty) {
-            // Must have a 'when' param
-            if let Some(ref when_param_value) = get_param!(&field.parameters, When) {
+        } else {
+            // Options are read as the type they contain.  Anything else (including pointers like
+            // Box<T> and tuples, which have their own PacketrsRead impls) is read as the field's
+            // type, or as a string if it has a 'string' param.
+            let field_read_call = if is_option(field_ty) {
+                generate_element_read(field, get_inner_type(field_ty), &read_context)
+            } else if let Some(encoding) = get_param!(&field.parameters, StringEncoding) {
+                generate_string_read(field, field_ty, encoding)
+            } else {
+                generate_read_call(field, field_ty, &read_context)
+            };
+            if is_option(field_ty) {
+                // Must have a 'when' param
+                if let Some(ref when_param_value) = get_param!(&field.parameters, When) {
+                    let absent_value = default_value.map_or(quote! { None }, |d| quote! { #d });
+                    quote! {
+                        if #when_param_value {
+                            Ok(Some(#field_read_call?))
+                        } else {
+                            Ok(#absent_value)
+                        }
+                    }
+                } else {
+                    panic!("Field {:?} is an Option, either a 'custom_reader' or a 'when' param is required", field_name);
+                }
+            } else if let (Some(when_param_value), Some(default_value)) =
+                (get_param!(&field.parameters, When), default_value)
+            {
                 quote! {
                     if #when_param_value {
-                        Ok(Some(#field_read_call?))
+                        #field_read_call
                     } else {
-                        Ok(None)
+                        Ok(#default_value)
                     }
                 }
             } else {
-                panic!("Field {:?} is an Option, either a 'custom_reader' or a 'when' param is required", field_name);
+                quote! {
+                    #field_read_call
+                }
             }
+        }
+    };
+
+    let read_call = if let Some(map) = get_param!(&field.parameters, Map) {
+        quote! {
+            #read_call.map(#map)
+        }
+    } else if let Some(try_map) = get_param!(&field.parameters, TryMap) {
+        quote! {
+            #read_call.and_then(|v| (#try_map)(v).map_err(|e| ::#crate_name::anyhow::anyhow!(e)))
+        }
+    } else {
+        read_call
+    };
+
+    // A peeked value is read and then the cursor is moved back to where it was before the read
+    let read_call = if has_param!(&field.parameters, Peek)
+        || get_param!(&field.parameters, PeekBits).is_some()
+    {
+        quote! {
+            (|| {
+                let __packetrs_position = ::std::io::Seek::stream_position(buf)?;
+                let value = #read_call;
+                ::std::io::Seek::seek(buf, ::std::io::SeekFrom::Start(__packetrs_position))?;
+                value
+            })()
+        }
+    } else {
+        read_call
+    };
+
+    // A field with an offset is read from that position (in bytes, relative to the start of the
+    // type being read or of the buffer), and then the cursor is either moved back to where it was
+    // or left after the field.
+    let read_call = if let Some(offset) = get_param!(&field.parameters, At) {
+        let start = if has_param!(&field.parameters, SeekFromStart) {
+            quote! { 0u64 }
         } else {
-            quote! {
-                #field_read_call
-            }
+            quote! { __packetrs_start }
+        };
+        let restore = match get_param!(&field.parameters, SeekMode).map(|m| m.value()) {
+            Some(ref mode) if mode == "advance" => TokenStream::new(),
+            _ => quote! {
+                ::std::io::Seek::seek(buf, ::std::io::SeekFrom::Start(__packetrs_position))?;
+            },
+        };
+        quote! {
+            (|| {
+                let __packetrs_offset: u64 = ::core::convert::TryInto::<u64>::try_into(#offset)
+                    .map_err(|_| ::#crate_name::anyhow::anyhow!("'at' value doesn't fit in a u64"))?;
+                let __packetrs_position = ::std::io::Seek::stream_position(buf)?;
+                ::std::io::Seek::seek(buf, ::std::io::SeekFrom::Start(#start + __packetrs_offset * 8))?;
+                let value = #read_call;
+                #restore
+                value
+            })()
         }
+    } else {
+        read_call
     };
 
-    // If there is a fixed value param, generate the assertion
+    read_call
+}
+
+fn generate_field_read(field: &PacketRsField) -> TokenStream {
+    let field_name = &field.name;
+    let error_context = field_name
+        .as_ref()
+        .unwrap_or_else(|| panic!("Unable to get name of field for error_context {:#?}", field))
+        .to_string();
+
+    if let Some(ref read_value) = get_param!(&field.parameters, ReadValue) {
+        return quote! {
+            let #field_name = #read_value;
+        };
+    }
+
+    if has_param!(&field.parameters, Skip) {
+        return quote! {
+            let #field_name = ::core::default::Default::default();
+        };
+    }
+
+    // Without a 'when', a default value means the field isn't read at all.  With one, it's used
+    // as the value when the 'when' expression is false.
+    let default_value = get_param!(&field.parameters, Default);
+    if let (Some(default_value), None) = (default_value, get_param!(&field.parameters, When)) {
+        return quote! {
+            let #field_name = #default_value;
+        };
+    }
+
+    let read_call = generate_value_read(field);
+
+    // If there is a fixed value param, generate the assertion.  A literal fixed value is converted
+    // to the field's type, in case it's a ux type.
     let fixed_value_assertion = if let Some(fixed_value) = get_param!(&field.parameters, Fixed) {
         let field_name_str = field_name.as_ref().unwrap().to_string();
-        let fixed_value = syn::parse_str::<syn::Expr>(fixed_value.value().as_ref()).unwrap();
+        let fixed_value = match syn::parse_str::<syn::Expr>(fixed_value.value().as_ref()).unwrap() {
+            syn::Expr::Lit(syn::ExprLit { ref lit, .. }) => generate_typed_literal(field.ty, lit),
+            fixed_value => quote! { #fixed_value },
+        };
         quote! {
             if #field_name != #fixed_value {
                 bail!("{} value didn't match: expected {}, got {}", #field_name_str, #fixed_value, #field_name);
@@ -156,10 +599,15 @@ fn generate_field_read(field: &PacketRsField) -> TokenStream {
     } else {
         TokenStream::new()
     };
-    // If there is an assert expression, generate the assertion
+    // If there is an assert expression, generate the assertion.  For a ux field, the literals the
+    // value is compared against in an assert closure are converted to the field's type.
     let assertion = if let Some(assertion) = get_param!(&field.parameters, Assert) {
         let field_name_str = field_name.as_ref().unwrap().to_string();
         let assertion_str = quote! { #assertion }.to_string();
+        let assertion = match get_ux_backing_primitive(field.ty) {
+            Some(_) => coerce_compared_literals(assertion, field.ty),
+            None => assertion.clone(),
+        };
         quote! {
             let assert_func = #assertion;
             if !assert_func(#field_name) {
@@ -177,6 +625,24 @@ fn generate_field_read(field: &PacketRsField) -> TokenStream {
     }
 }
 
+/// Return true if the given params are those of a field that's read at an offset relative to the
+/// start of the type it belongs to.
+fn is_read_at_relative_offset(params: &[PacketRsAttributeParam]) -> bool {
+    get_param!(params, At).is_some() && !has_param!(params, SeekFromStart)
+}
+
+/// Generate the capture of the position (in bits) at which the read of a type starts into
+/// '__packetrs_start', if any of its fields are read at an offset relative to it.
+fn generate_start_capture(needs_start: bool) -> TokenStream {
+    if needs_start {
+        quote! {
+            let __packetrs_start = ::std::io::Seek::stream_position(buf)?;
+        }
+    } else {
+        TokenStream::new()
+    }
+}
+
 /// Return a proc_macro2::TokenStream that includes local assignments for the read value of each of
 /// the given fields.
 fn generate_field_reads(fields: &[PacketRsField]) -> TokenStream {
@@ -190,6 +656,67 @@ fn generate_field_reads(fields: &[PacketRsField]) -> TokenStream {
     }
 }
 
+/// Return a string representation of the given path, e.g. "path::to_fn", for use as error context
+fn path_to_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect::<Vec<String>>()
+        .join("::")
+}
+
+/// Wrap the given read body (an expression of type PacketRsResult<Self>) with calls to any
+/// 'pre_read', 'post_read' and 'validate' hooks defined in the given parameters.
+fn generate_read_hooks(
+    type_name: &syn::Ident,
+    parameters: &[PacketRsAttributeParam],
+    read_body: TokenStream,
+) -> TokenStream {
+    let crate_name = get_crate_name();
+    let pre_read = get_param!(parameters, PreRead);
+    let post_read = get_param!(parameters, PostRead);
+    let validate = get_param!(parameters, Validate);
+    if pre_read.is_none() && post_read.is_none() && validate.is_none() {
+        return read_body;
+    }
+
+    let pre_read_call = pre_read.map_or(TokenStream::new(), |pre_read| {
+        let error_context = path_to_string(pre_read);
+        quote! {
+            #pre_read(buf).context(#error_context)?;
+        }
+    });
+    let (value_binding, post_read_call) = post_read.map_or(
+        (quote! { let value }, TokenStream::new()),
+        |post_read| {
+            let error_context = path_to_string(post_read);
+            (
+                quote! { let mut value },
+                quote! {
+                    #post_read(&mut value, buf).context(#error_context)?;
+                },
+            )
+        },
+    );
+    let validate_call = validate.map_or(TokenStream::new(), |validate| {
+        let error_context = format!("{} failed validation", type_name);
+        quote! {
+            #validate(&value)
+                .map_err(|e| ::#crate_name::anyhow::anyhow!(e))
+                .context(#error_context)?;
+        }
+    });
+
+    quote! {
+        #pre_read_call
+        let result: ::#crate_name::error::PacketRsResult<Self> = { #read_body };
+        #value_binding = result?;
+        #post_read_call
+        #validate_call
+        Ok(value)
+    }
+}
+
 /// Given a Vec of FnArgs, generate the context variable assignments, e.g.:
 /// let foo = ctx.0;
 /// let bar = ctx.1;
@@ -219,9 +746,96 @@ fn generate_context_assignments(context: &[syn::FnArg]) -> TokenStream {
     }
 }
 
+/// Generate the struct used to pass the given required context by name, and its PacketrsNamedCtx
+/// impl.  Both are in an anonymous const block, so the struct can't clash with the user's items and
+/// can only be named as the impl's 'Named' type.  If any of the required context arguments isn't a
+/// simple 'name: type' (so there's no name to use), or the context uses explicit lifetimes, then
+/// nothing is generated and the context can only be passed positionally.
+fn generate_named_ctx(
+    type_name: &syn::Ident,
+    vis: &syn::Visibility,
+    ctx_type: &syn::Type,
+    required_ctx: &[syn::FnArg],
+) -> TokenStream {
+    let crate_name = get_crate_name();
+    let names = required_ctx
+        .iter()
+        .map(get_var_name_from_fn_arg)
+        .collect::<Option<Vec<&syn::Ident>>>();
+    let names = match names {
+        Some(names) => names,
+        None => return TokenStream::new(),
+    };
+    let types = get_ctx_types(required_ctx);
+    let named_ctx_name = format_ident!("{}PacketrsCtx", type_name);
+
+    // Elided lifetimes in the context all become the context lifetime, which the named struct then
+    // needs to be generic over
+    let lifetimes = get_lifetimes(ctx_type);
+    let (impl_generics, trait_lifetime, struct_generics) = if lifetimes.is_empty() {
+        (quote! {}, quote! { '_ }, quote! {})
+    } else if lifetimes == vec![ctx_lifetime()] {
+        let lifetime = ctx_lifetime();
+        (quote! { <#lifetime> }, quote! { #lifetime }, quote! { <#lifetime> })
+    } else {
+        return TokenStream::new();
+    };
+
+    quote! {
+        const _: () = {
+            #vis struct #named_ctx_name #struct_generics {
+                #(#vis #names: #types),*
+            }
+
+            impl #impl_generics ::#crate_name::packetrs_read::PacketrsNamedCtx<#trait_lifetime> for #type_name {
+                type Ctx = #ctx_type;
+                type Named = #named_ctx_name #struct_generics;
+
+                fn into_ctx(named: Self::Named) -> Self::Ctx {
+                    (#(named.#names,)*)
+                }
+            }
+        };
+    }
+}
+
+/// Generate the PacketrsRead impl for the given type.  'read' just calls 'read_with_options' with
+/// the default options, and 'read_with_options' tracks the nesting depth around the given body.
+/// If the context contains references, the impl is generic over their lifetimes.
+fn generate_read_impl(
+    type_name: &syn::Ident,
+    ctx_type: &syn::Type,
+    context_assignments: TokenStream,
+    read_body: TokenStream,
+) -> TokenStream {
+    let crate_name = get_crate_name();
+    let lifetimes = get_lifetimes(ctx_type);
+    let impl_generics = if lifetimes.is_empty() {
+        quote! {}
+    } else {
+        quote! { <#(#lifetimes),*> }
+    };
+    quote! {
+        impl #impl_generics ::#crate_name::packetrs_read::PacketrsRead<#ctx_type> for #type_name {
+            fn read<T: ::#crate_name::b3::byte_order::ByteOrder>(buf: &mut ::#crate_name::b3::bit_cursor::BitCursor, ctx: #ctx_type) -> ::#crate_name::error::PacketRsResult<Self> {
+                <Self as ::#crate_name::packetrs_read::PacketrsRead<#ctx_type>>::read_with_options::<T>(buf, ctx, &mut ::#crate_name::read_options::ReadOptions::default())
+            }
+
+            fn read_with_options<T: ::#crate_name::b3::byte_order::ByteOrder>(buf: &mut ::#crate_name::b3::bit_cursor::BitCursor, ctx: #ctx_type, read_options: &mut ::#crate_name::read_options::ReadOptions) -> ::#crate_name::error::PacketRsResult<Self> {
+                read_options.enter()?;
+                let result = (|| -> ::#crate_name::error::PacketRsResult<Self> {
+                    #context_assignments
+                    #read_body
+                })();
+                read_options.exit();
+                result
+            }
+        }
+    }
+}
+
 /// Generate the PacketrsRead method for the given struct.
 pub(crate) fn generate_struct(packetrs_struct: &PacketRsStruct) -> TokenStream {
-    let crate_name = get_crate_name();
     let expected_context = get_param!(&packetrs_struct.parameters, RequiredContext);
     let ctx_type = get_ctx_type(&expected_context).expect("Error getting ctx type");
     let struct_name = &packetrs_struct.name;
@@ -232,6 +846,9 @@ pub(crate) fn generate_struct(packetrs_struct: &PacketRsStruct) -> TokenStream {
         proc_macro2::TokenStream::new()
     };
 
+    // The start position is captured before any 'pre_read' hook, so that relative offsets are
+    // from the actual start of the struct
+    let mut start_capture = TokenStream::new();
     let read_body = if let Some(ref custom_reader_value) =
         get_param!(&packetrs_struct.parameters, CustomReader)
     {
@@ -274,9 +891,16 @@ pub(crate) fn generate_struct(packetrs_struct: &PacketRsStruct) -> TokenStream {
                 })
                 .collect()
         };
+        start_capture = generate_start_capture(
+            fields
+                .iter()
+                .any(|f| is_read_at_relative_offset(&f.parameters)),
+        );
         let reads = generate_field_reads(&fields);
+        // All fields are read into locals, but 'temp' fields don't exist in the struct itself
         let field_names = fields
             .iter()
+            .filter(|f| !has_param!(&f.parameters, Temp))
             .map(|f| f.name.as_ref().expect("Unable to get name of named field"));
         let creation = if are_fields_named(&packetrs_struct.fields) {
             quote! {
@@ -292,14 +916,20 @@ pub(crate) fn generate_struct(packetrs_struct: &PacketRsStruct) -> TokenStream {
             #creation
         }
     };
+    let read_body = generate_read_hooks(struct_name, &packetrs_struct.parameters, read_body);
+    let read_body = quote! {
+        #start_capture
+        #read_body
+    };
+
+    let named_ctx = expected_context.map_or(TokenStream::new(), |required_ctx| {
+        generate_named_ctx(struct_name, packetrs_struct.vis, &ctx_type, required_ctx)
+    });
+    let read_impl = generate_read_impl(struct_name, &ctx_type, context_assignments, read_body);
 
     quote! {
-        impl ::#crate_name::packetrs_read::PacketrsRead<#ctx_type> for #struct_name {
-            fn read<T: ::#crate_name::b3::byte_order::ByteOrder>(buf: &mut ::#crate_name::b3::bit_cursor::BitCursor, ctx: #ctx_type) -> ::#crate_name::error::PacketRsResult<Self> {
-                #context_assignments
-                #read_body
-            }
-        }
+        #read_impl
+        #named_ctx
     }
 }
 
@@ -325,11 +955,14 @@ fn generate_match_arm(enum_name: &syn::Ident, variant: &PacketRsEnumVariant) ->
     };
 
     let reads = generate_field_reads(&fields);
-    let field_names = fields.iter().map(|f| {
-        f.name
-            .as_ref()
-            .unwrap_or_else(|| panic!("Found unnamed fields amongst named fields: {:#?}", f))
-    });
+    let field_names = fields
+        .iter()
+        .filter(|f| !has_param!(&f.parameters, Temp))
+        .map(|f| {
+            f.name
+                .as_ref()
+                .unwrap_or_else(|| panic!("Found unnamed fields amongst named fields: {:#?}", f))
+        });
     if variant.fields.is_empty() {
         quote! {
             #key => {
@@ -359,8 +992,204 @@ fn generate_match_arm(enum_name: &syn::Ident, variant: &PacketRsEnumVariant) ->
     }
 }
 
-pub(crate) fn generate_enum(packetrs_enum: &PacketRsEnum) -> TokenStream {
+/// Generate the read of a value that belongs to the enum itself rather than to one of its variants
+/// (e.g. its tag) into a local with the given name.  It's read like a field, using the enum's byte
+/// order and peek params.
+fn generate_enum_value_read(packetrs_enum: &PacketRsEnum, name: &str, ty: &syn::Type) -> TokenStream {
+    let field = PacketRsField {
+        name: Some(format_ident!("{}", name)),
+        ty,
+        parameters: packetrs_enum
+            .parameters
+            .iter()
+            .filter(|p| {
+                matches!(
+                    p,
+                    PacketRsAttributeParam::ByteOrder(_)
+                        | PacketRsAttributeParam::Peek
+                        | PacketRsAttributeParam::PeekBits(_)
+                )
+            })
+            .cloned()
+            .collect(),
+    };
+    generate_field_read(&field)
+}
+
+/// If the given type is a ux type (e.g. u4, but not a native type like u8), return the native type
+/// that backs it.  Untyped integer literals can't be used as ux values, so they're either converted
+/// into the ux type or compared against the backing native value instead.
+fn get_ux_backing_primitive(ty: &syn::Type) -> Option<syn::Ident> {
+    get_backing_primitive(ty)
+        .filter(|primitive| !matches!(ty, syn::Type::Path(tp) if tp.path.is_ident(primitive)))
+}
+
+/// Generate a value of the given type from a literal: ux types (e.g. u4) are constructed from the
+/// literal, and anything else is assumed to be a type the literal can be used as directly.
+fn generate_typed_literal(ty: &syn::Type, lit: &syn::Lit) -> TokenStream {
+    if get_ux_backing_primitive(ty).is_some() {
+        quote! { <#ty>::new(#lit) }
+    } else {
+        quote! { #lit }
+    }
+}
+
+/// Generate the conversion of the given value of the given type into its backing native type, if
+/// it's a ux type, so it can be matched or compared against plain literals.
+fn generate_as_primitive(ty: &syn::Type, value: TokenStream) -> TokenStream {
+    if let Some(primitive) = get_ux_backing_primitive(ty) {
+        quote! { <#primitive as ::core::convert::From<#ty>>::from(#value) }
+    } else {
+        value
+    }
+}
+
+/// Return the type of the given enum's key, if it can be determined: its repr or tag type, or the
+/// type of the 'required_ctx' argument used as its key.  Keys that are references or other
+/// expressions (e.g. "left + right") have no known type.
+fn get_enum_key_type<'a>(packetrs_enum: &'a PacketRsEnum) -> Option<&'a syn::Type> {
+    if let Some(repr) = get_param!(&packetrs_enum.parameters, Repr) {
+        return Some(repr);
+    }
+    let tag = get_param!(&packetrs_enum.parameters, EnumTag);
+    let key_ident = match get_param!(&packetrs_enum.parameters, EnumKey) {
+        Some(syn::Expr::Path(key_path)) => key_path.path.get_ident()?,
+        Some(_) => return None,
+        None => return tag,
+    };
+    if key_ident == "tag" && tag.is_some() {
+        return tag;
+    }
+    get_param!(&packetrs_enum.parameters, RequiredContext)?
+        .iter()
+        .find(|fn_arg| get_var_name_from_fn_arg(fn_arg) == Some(key_ident))
+        .and_then(get_var_type_from_fn_arg)
+        .filter(|ty| !matches!(ty, syn::Type::Reference(_)))
+}
+
+/// Generate a 'packetrs_id' method returning the id of an enum value's variant (i.e. the value of
+/// the key that selected it), and a 'PACKETRS_IDS' const with the ids of all the variants.  These
+/// are only generated when the key's type is known and every variant's id is a single literal (a
+/// 'repr' enum's catch-all variant returns the value it holds).
+fn generate_enum_ids(packetrs_enum: &PacketRsEnum) -> TokenStream {
+    let enum_name = packetrs_enum.name;
+    let vis = packetrs_enum.vis;
+    let key_type = match get_enum_key_type(packetrs_enum) {
+        Some(key_type) => key_type,
+        None => return TokenStream::new(),
+    };
+    let is_repr = get_param!(&packetrs_enum.parameters, Repr).is_some();
+
+    let mut ids = Vec::new();
+    let mut id_arms = Vec::new();
+    for variant in &packetrs_enum.variants {
+        let variant_name = variant.name;
+        match get_param!(&variant.parameters, EnumId).map(|id| id.get_literal()) {
+            Some(Some(lit)) => {
+                let id = generate_typed_literal(key_type, lit);
+                id_arms.push(quote! { #enum_name::#variant_name { .. } => #id });
+                ids.push(id);
+            }
+            None if is_repr => {
+                id_arms.push(quote! { #enum_name::#variant_name(value) => *value });
+            }
+            _ => return TokenStream::new(),
+        }
+    }
+
+    quote! {
+        impl #enum_name {
+            /// The ids of all of the variants
+            #vis const PACKETRS_IDS: &[#key_type] = &[#(#ids),*];
+
+            /// The id of this value's variant
+            #vis fn packetrs_id(&self) -> #key_type {
+                match self {
+                    #(#id_arms,)*
+                }
+            }
+        }
+    }
+}
+
+/// Generate the read body for a fieldless enum with a 'repr', along with its conversions to and
+/// from the repr type.  The body reads a value of the repr type and converts it via the generated
+/// TryFrom impl, which matches the value's backing primitive (e.g. u8 for u4) against each
+/// variant's id.  Values that don't match any id go to the catch-all variant, if there is one, and
+/// are an UnknownEnumValue error otherwise.
+fn generate_repr_enum(packetrs_enum: &PacketRsEnum, repr: &syn::Type) -> (TokenStream, TokenStream) {
     let crate_name = get_crate_name();
+    let enum_name = packetrs_enum.name;
+    let enum_name_str = enum_name.to_string();
+    let primitive = get_backing_primitive(repr).unwrap_or_else(|| {
+        panic!("Enum {} has an invalid 'repr': it must be an unsigned integer type", enum_name)
+    });
+
+    let mut from_repr_arms = Vec::new();
+    let mut into_repr_arms = Vec::new();
+    let mut catch_all = None;
+    for variant in &packetrs_enum.variants {
+        let variant_name = variant.name;
+        match (get_param!(&variant.parameters, EnumId), variant.fields.len()) {
+            (Some(id), 0) => {
+                let lit = id.get_literal().unwrap_or_else(|| {
+                    panic!("Variant {} of 'repr' enum {} must have a single literal 'id'", variant_name, enum_name)
+                });
+                let repr_value = generate_typed_literal(repr, lit);
+                from_repr_arms.push(quote! { #id => ::core::result::Result::Ok(#enum_name::#variant_name) });
+                into_repr_arms.push(quote! { #enum_name::#variant_name => #repr_value });
+            }
+            (None, 1) if !are_fields_named(&variant.fields) && catch_all.is_none() => {
+                catch_all = Some(variant_name);
+                into_repr_arms.push(quote! { #enum_name::#variant_name(value) => value });
+            }
+            _ => panic!(
+                "Variant {} of 'repr' enum {} must either be a unit variant with an 'id' or the only catch-all variant with a single unnamed field",
+                variant_name, enum_name
+            ),
+        }
+    }
+    let unknown_arm = if let Some(catch_all) = catch_all {
+        quote! { _ => ::core::result::Result::Ok(#enum_name::#catch_all(value)) }
+    } else {
+        quote! {
+            _ => ::core::result::Result::Err(::#crate_name::error::UnknownEnumValue {
+                enum_name: #enum_name_str,
+                value: <u64 as ::core::convert::From<#primitive>>::from(primitive),
+            })
+        }
+    };
+
+    let repr_read = generate_enum_value_read(packetrs_enum, "repr", repr);
+    let body = quote! {
+        #repr_read
+        Ok(<Self as ::core::convert::TryFrom<#repr>>::try_from(repr)?)
+    };
+    let conversions = quote! {
+        impl ::core::convert::TryFrom<#repr> for #enum_name {
+            type Error = ::#crate_name::error::UnknownEnumValue;
+
+            fn try_from(value: #repr) -> ::core::result::Result<Self, Self::Error> {
+                let primitive = <#primitive as ::core::convert::From<#repr>>::from(value);
+                match primitive {
+                    #(#from_repr_arms,)*
+                    #unknown_arm
+                }
+            }
+        }
+
+        impl ::core::convert::From<#enum_name> for #repr {
+            fn from(value: #enum_name) -> Self {
+                match value {
+                    #(#into_repr_arms,)*
+                }
+            }
+        }
+    };
+    (body, conversions)
+}
+
+pub(crate) fn generate_enum(packetrs_enum: &PacketRsEnum) -> TokenStream {
     let expected_context = get_param!(&packetrs_enum.parameters, RequiredContext);
     let context_assignments = if let Some(required_ctx) = expected_context {
         generate_context_assignments(required_ctx)
@@ -372,6 +1201,10 @@ pub(crate) fn generate_enum(packetrs_enum: &PacketRsEnum) -> TokenStream {
 
     // If there is a custom reader, then the function body will just be a passthrough call to
     // that custom reader function.  Otherwise it will be a match expression.
+    let mut conversions = TokenStream::new();
+    // The start position is captured before any 'pre_read' hook, so that relative offsets are
+    // from the actual start of the enum
+    let mut start_capture = TokenStream::new();
     let body = if let Some(ref custom_reader_value) =
         get_param!(&packetrs_enum.parameters, CustomReader)
     {
@@ -394,33 +1227,87 @@ pub(crate) fn generate_enum(packetrs_enum: &PacketRsEnum) -> TokenStream {
         quote! {
             #custom_reader_value(buf, #ctx_args).context(#error_context)
         }
+    } else if let Some(repr) = get_param!(&packetrs_enum.parameters, Repr) {
+        if get_param!(&packetrs_enum.parameters, EnumKey).is_some()
+            || get_param!(&packetrs_enum.parameters, EnumTag).is_some()
+        {
+            panic!("Enum {} has a 'repr': it can't also have a 'key' or 'tag'", enum_name);
+        }
+        let (body, repr_conversions) = generate_repr_enum(packetrs_enum, repr);
+        conversions = repr_conversions;
+        body
     } else {
-        let enum_variant_key = get_param!(&packetrs_enum.parameters, EnumKey)
-            .unwrap_or_else(|| panic!("Enum {} is missing 'key' attribute", enum_name));
-        
+        // If the enum reads its own tag, it's read like a field named 'tag' (with the enum's byte
+        // order) so that it's available to the key expression and the variants' fields.
+        let enum_tag = get_param!(&packetrs_enum.parameters, EnumTag);
+        // Offsets of the variants' fields are relative to the start of the enum (i.e. before its
+        // tag).  Unnamed fields get their params from the variant.
+        start_capture = generate_start_capture(packetrs_enum.variants.iter().any(|v| {
+            is_read_at_relative_offset(&v.parameters)
+                || v.fields
+                    .iter()
+                    .any(|f| is_read_at_relative_offset(&f.parameters))
+        }));
+        let tag_read = enum_tag.map_or(TokenStream::new(), |tag_ty| {
+            generate_enum_value_read(packetrs_enum, "tag", tag_ty)
+        });
+        let enum_variant_key = match (get_param!(&packetrs_enum.parameters, EnumKey), enum_tag) {
+            (Some(key), _) => quote! { #key },
+            (None, Some(_)) => quote! { tag },
+            (None, None) => panic!("Enum {} is missing 'key' (or 'tag') attribute", enum_name),
+        };
+        // A ux key is matched via its backing native type, so the ids can be plain literals
+        let enum_variant_key = match get_enum_key_type(packetrs_enum) {
+            Some(key_type) => generate_as_primitive(key_type, enum_variant_key),
+            None => enum_variant_key,
+        };
+
         let match_arms = packetrs_enum
             .variants
             .iter()
             .map(|v| generate_match_arm(enum_name, v))
             .collect::<Vec<proc_macro2::TokenStream>>();
+        // A key that doesn't match any variant (e.g. an unknown tag read from the buffer) is an
+        // error: an UnknownEnumValue if the key is an unsigned integer, like for a 'repr' enum
+        let crate_name = get_crate_name();
+        let enum_name_str = enum_name.to_string();
+        let unknown_arm = match get_enum_key_type(packetrs_enum).and_then(get_backing_primitive) {
+            Some(primitive) => quote! {
+                v => Err(::#crate_name::error::UnknownEnumValue {
+                    enum_name: #enum_name_str,
+                    value: <u64 as ::core::convert::From<#primitive>>::from(v),
+                }.into())
+            },
+            None => quote! {
+                v => Err(::#crate_name::anyhow::anyhow!("{:?} is not a known {} value", v, #enum_name_str))
+            },
+        };
 
         quote! {
+            #tag_read
             match #enum_variant_key {
                 #(#match_arms),*,
-                v @ _ => {
-                    todo!("Value of {:?} is not implemented", v);
-                }
+                #unknown_arm
             }
         }
     };
+    let body = generate_read_hooks(enum_name, &packetrs_enum.parameters, body);
+    let body = quote! {
+        #start_capture
+        #body
+    };
+
+    let named_ctx = expected_context.map_or(TokenStream::new(), |required_ctx| {
+        generate_named_ctx(enum_name, packetrs_enum.vis, &ctx_type, required_ctx)
+    });
+    let read_impl = generate_read_impl(enum_name, &ctx_type, context_assignments, body);
+    let ids = generate_enum_ids(packetrs_enum);
 
     quote! {
-        impl ::#crate_name::packetrs_read::PacketrsRead<#ctx_type> for #enum_name {
-            fn read<T: ::#crate_name::b3::byte_order::ByteOrder>(buf: &mut ::#crate_name::b3::bit_cursor::BitCursor, ctx: #ctx_type) -> ::#crate_name::error::PacketRsResult<Self> {
-                #context_assignments
-                #body
-            }
-        }
+        #read_impl
+        #named_ctx
+        #conversions
+        #ids
     }
 }
 
@@ -428,6 +1315,31 @@ pub(crate) fn generate_enum(packetrs_enum: &PacketRsEnum) -> TokenStream {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_get_named_ctx_args() {
+        let exprs = vec![
+            syn::parse_str::<syn::Expr>("length = attr_len").unwrap(),
+            syn::parse_str::<syn::Expr>("message_type = attr_type + 1").unwrap(),
+        ];
+        let result = get_named_ctx_args(&exprs).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].0, "length");
+        assert_eq!(result[1].0, "message_type");
+        assert_eq!(
+            result[1].1,
+            &syn::parse_str::<syn::Expr>("attr_type + 1").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_get_named_ctx_args_positional() {
+        let exprs = vec![
+            syn::parse_str::<syn::Expr>("attr_len").unwrap(),
+            syn::parse_str::<syn::Expr>("attr_type == 1").unwrap(),
+        ];
+        assert!(get_named_ctx_args(&exprs).is_none());
+    }
+
     #[test]
     fn test_generate_context_assignments() {
         let fn_arg = syn::parse_str::<syn::FnArg>("foo: u32").unwrap();
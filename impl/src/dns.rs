@@ -0,0 +1,158 @@
+use std::{
+    fmt,
+    io::{Seek, SeekFrom},
+};
+
+use anyhow::bail;
+use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts, byte_order::ByteOrder};
+
+use crate::{error::PacketRsResult, packetrs_read::PacketrsRead};
+
+/// The maximum length of an encoded name, in bytes (RFC 1035 section 2.3.4)
+const MAX_NAME_LEN: usize = 255;
+
+/// A domain name, as encoded in DNS messages (RFC 1035 section 4.1.4): a sequence of
+/// length-prefixed labels ending with an empty label, where the end of the name may instead be a
+/// pointer to (the rest of) a name elsewhere in the message.
+///
+/// Pointers are offsets from the start of the message, so the buffer being read must contain the
+/// whole message.  Its position in the buffer, in bytes, is the context: e.g. when the message is
+/// the payload of a UDP packet read from the same buffer, a type containing names can take it as
+/// `required_ctx = "message_start: u64"` and pass it on with `ctx = "message_start"`.  Reading
+/// without context assumes the message starts at position 0 (as it does when reading via
+/// [`from_bytes`](crate::packetrs_read::PacketrsReadExt::from_bytes)).  After a read, the cursor
+/// is left after the first pointer (or the end of the name, if there isn't one).  Pointer loops
+/// are detected and returned as errors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DnsName {
+    pub labels: Vec<Vec<u8>>,
+}
+
+impl PacketrsRead<()> for DnsName {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, _: ()) -> PacketRsResult<Self> {
+        <Self as PacketrsRead<(u64,)>>::read::<T>(buf, (0,))
+    }
+}
+
+impl PacketrsRead<(u64,)> for DnsName {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, (message_start,): (u64,)) -> PacketRsResult<Self> {
+        let mut labels = Vec::new();
+        let mut name_len = 0;
+        // Where the cursor is left once the whole name has been read
+        let mut end_position = None;
+        let mut pointers_followed = Vec::new();
+        loop {
+            let len = buf.read_u8()?;
+            match len & 0xc0 {
+                0x00 if len == 0 => break,
+                0x00 => {
+                    name_len += len as usize + 1;
+                    if name_len > MAX_NAME_LEN {
+                        bail!("Name is longer than the maximum of {} bytes", MAX_NAME_LEN);
+                    }
+                    let mut label = Vec::with_capacity(len as usize);
+                    for _ in 0..len {
+                        label.push(buf.read_u8()?);
+                    }
+                    labels.push(label);
+                }
+                0xc0 => {
+                    let offset = (((len & 0x3f) as u64) << 8) | buf.read_u8()? as u64;
+                    if pointers_followed.contains(&offset) {
+                        bail!("Name compression pointer loop at offset {}", offset);
+                    }
+                    pointers_followed.push(offset);
+                    if end_position.is_none() {
+                        end_position = Some(buf.stream_position()?);
+                    }
+                    let position = message_start
+                        .checked_add(offset)
+                        .and_then(|position| position.checked_mul(8));
+                    match position {
+                        Some(position) => buf.seek(SeekFrom::Start(position))?,
+                        None => bail!("Name compression pointer to offset {} overflows", offset),
+                    };
+                }
+                label_type => bail!("Unsupported label type {:#x}", label_type),
+            }
+        }
+        if let Some(end_position) = end_position {
+            buf.seek(SeekFrom::Start(end_position))?;
+        }
+        Ok(DnsName { labels })
+    }
+}
+
+/// Displays the name as its labels separated by dots, e.g. "www.example.com" (or "." for the root
+/// name).  Any bytes that aren't valid UTF-8 are replaced.
+impl fmt::Display for DnsName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.labels.is_empty() {
+            return write!(f, ".");
+        }
+        for (idx, label) in self.labels.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", String::from_utf8_lossy(label))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use b3::byte_order::NetworkOrder;
+
+    use super::*;
+
+    #[test]
+    fn test_read_compressed_name() {
+        let data: Vec<u8> = vec![
+            // "example.com" at offset 0
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0,
+            // "www" followed by a pointer to offset 0, and then a byte after the name
+            3, b'w', b'w', b'w', 0xc0, 0x00, 0xff,
+        ];
+        let mut buf = BitCursor::from_vec(data);
+        let name = DnsName::read::<NetworkOrder>(&mut buf, ()).unwrap();
+        assert_eq!(name.to_string(), "example.com");
+
+        let name = DnsName::read::<NetworkOrder>(&mut buf, ()).unwrap();
+        assert_eq!(name.to_string(), "www.example.com");
+        // The cursor is left after the pointer
+        assert_eq!(buf.read_u8().unwrap(), 0xff);
+    }
+
+    #[test]
+    fn test_read_compressed_name_with_message_start() {
+        let data: Vec<u8> = vec![
+            // Two bytes before the message, which pointers are relative to
+            0xaa, 0xbb,
+            // "example.com" at offset 0 of the message
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0,
+            // "www" followed by a pointer to offset 0
+            3, b'w', b'w', b'w', 0xc0, 0x00,
+        ];
+        let mut buf = BitCursor::from_vec(data);
+        buf.seek(SeekFrom::Start(15 * 8)).unwrap();
+        let name = DnsName::read::<NetworkOrder>(&mut buf, (2,)).unwrap();
+        assert_eq!(name.to_string(), "www.example.com");
+        assert_eq!(buf.bits_remaining(), 0);
+    }
+
+    #[test]
+    fn test_read_name_pointer_loop() {
+        let data: Vec<u8> = vec![3, b'w', b'w', b'w', 0xc0, 0x00];
+        let mut buf = BitCursor::from_vec(data);
+        assert!(DnsName::read::<NetworkOrder>(&mut buf, ()).is_err());
+    }
+
+    #[test]
+    fn test_read_root_name() {
+        let mut buf = BitCursor::from_vec(vec![0]);
+        let name = DnsName::read::<NetworkOrder>(&mut buf, ()).unwrap();
+        assert_eq!(name, DnsName::default());
+        assert_eq!(name.to_string(), ".");
+    }
+}
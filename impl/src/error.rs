@@ -1 +1,60 @@
+use std::fmt;
+
 pub type PacketRsResult<T> = anyhow::Result<T>;
+
+/// The error returned when a read exceeds one of the limits set in
+/// [`ReadOptions`](crate::read_options::ReadOptions).  It's returned wrapped in the context of the
+/// read that failed, and can be retrieved via `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    /// A collection would have contained more than the maximum number of elements
+    CollectionLength { limit: usize, requested: usize },
+    /// More than the maximum total number of bytes would have been allocated for collection
+    /// elements
+    TotalAllocation { limit: usize, requested: usize },
+    /// Types were nested deeper than the maximum depth
+    Depth { limit: usize },
+    /// A 'while' loop ran for more than the maximum number of iterations
+    WhileIterations { limit: usize },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::CollectionLength { limit, requested } => write!(
+                f,
+                "collection length {} exceeds the limit of {}",
+                requested, limit
+            ),
+            LimitExceeded::TotalAllocation { limit, requested } => write!(
+                f,
+                "total allocation of {} bytes exceeds the limit of {}",
+                requested, limit
+            ),
+            LimitExceeded::Depth { limit } => {
+                write!(f, "nesting depth exceeds the limit of {}", limit)
+            }
+            LimitExceeded::WhileIterations { limit } => {
+                write!(f, "'while' iterations exceed the limit of {}", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// The error returned when the value read for an enum with a 'repr' (or converted into one via its
+/// generated `TryFrom` impl) doesn't correspond to any of its variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEnumValue {
+    pub enum_name: &'static str,
+    pub value: u64,
+}
+
+impl fmt::Display for UnknownEnumValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x} is not a known {} value", self.value, self.enum_name)
+    }
+}
+
+impl std::error::Error for UnknownEnumValue {}
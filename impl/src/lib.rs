@@ -1,37 +1,156 @@
 //! This crate implements the macro for `packetrs` and should not be used directly.
 
+pub mod bitmap;
 mod code_gen;
+pub mod dns;
 pub mod error;
 mod match_pat_guard;
 mod model_parse;
 mod model_types;
+pub mod ntp;
 pub mod packetrs_read;
+pub mod read_options;
+pub mod string;
 mod syn_helpers;
+mod validation;
+pub mod varint;
 
 use code_gen::generate_enum;
 use model_parse::parse_enum;
 use proc_macro2::TokenStream;
-use syn::DeriveInput;
+use quote::quote;
+use syn::{parse::Parser, punctuated::Punctuated, DeriveInput, Token};
 
-use crate::{code_gen::generate_struct, model_parse::parse_struct};
+use crate::{
+    code_gen::generate_struct,
+    model_parse::{parse_packetrs_attrs_from_attributes, parse_struct},
+    model_types::PacketRsAttributeParam,
+    validation::{validate_enum_ids, validate_param_values},
+};
 
 pub use ::anyhow;
 pub use b3;
 pub use b3::ux;
+#[cfg(feature = "smallvec")]
+pub use smallvec;
 
 #[doc(hidden)]
 pub fn derive_packetrs_read(item: TokenStream) -> std::result::Result<TokenStream, syn::Error> {
     let ast: DeriveInput = syn::parse2(item)?;
+    // A derive can't change the definition of the type, so it can't remove 'temp' fields
+    if let Some(temp_field) = get_fields(&ast.data).into_iter().find(|f| is_temp_field(f)) {
+        return Err(syn::Error::new_spanned(
+            temp_field,
+            "'temp' fields require #[packetrs_derive(PacketrsRead)] instead of #[derive(PacketrsRead)]",
+        ));
+    }
+    generate_packetrs_read(&ast)
+}
+
+/// The implementation of the packetrs_derive attribute: generates the same impls as the derive
+/// would, but also removes 'temp' fields (and the packetrs attributes, since there's no derive
+/// to declare them) from the definition of the type.
+#[doc(hidden)]
+pub fn packetrs_derive(
+    attr: TokenStream,
+    item: TokenStream,
+) -> std::result::Result<TokenStream, syn::Error> {
+    let derives = Punctuated::<syn::Path, Token![,]>::parse_terminated.parse2(attr)?;
+    if derives.is_empty() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "packetrs_derive requires the traits to derive, e.g. #[packetrs_derive(PacketrsRead)]",
+        ));
+    }
+    if let Some(unsupported) = derives.iter().find(|d| !d.is_ident("PacketrsRead")) {
+        return Err(syn::Error::new_spanned(
+            unsupported,
+            "Only PacketrsRead is supported by packetrs_derive",
+        ));
+    }
+
+    let mut ast: DeriveInput = syn::parse2(item)?;
+    // Unnamed fields are read (and the value constructed) by position, so they can't be removed
+    if let Some(temp_field) = get_fields(&ast.data)
+        .into_iter()
+        .find(|f| f.ident.is_none() && is_temp_field(f))
+    {
+        return Err(syn::Error::new_spanned(
+            temp_field,
+            "Only named fields can be 'temp' fields",
+        ));
+    }
+    let packetrs_read_impl = generate_packetrs_read(&ast)?;
+
+    strip_packetrs_attrs(&mut ast.attrs);
+    match ast.data {
+        syn::Data::Struct(ref mut s) => strip_fields(&mut s.fields),
+        syn::Data::Enum(ref mut e) => {
+            for variant in e.variants.iter_mut() {
+                strip_packetrs_attrs(&mut variant.attrs);
+                strip_fields(&mut variant.fields);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(quote! {
+        #ast
+        #packetrs_read_impl
+    })
+}
+
+fn get_fields(data: &syn::Data) -> Vec<&syn::Field> {
+    match data {
+        syn::Data::Struct(ref s) => s.fields.iter().collect(),
+        syn::Data::Enum(ref e) => e.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        syn::Data::Union(ref u) => u.fields.named.iter().collect(),
+    }
+}
+
+fn is_temp_field(field: &syn::Field) -> bool {
+    crate::has_param!(parse_packetrs_attrs_from_attributes(&field.attrs), Temp)
+}
+
+fn strip_packetrs_attrs(attrs: &mut Vec<syn::Attribute>) {
+    attrs.retain(|attr| !attr.path.is_ident("packetrs"));
+}
+
+/// Remove the 'temp' fields from the given fields, and the packetrs attributes from the rest
+fn strip_fields(fields: &mut syn::Fields) {
+    match fields {
+        syn::Fields::Named(ref mut named) => {
+            named.named = std::mem::take(&mut named.named)
+                .into_iter()
+                .filter(|f| !is_temp_field(f))
+                .collect();
+        }
+        syn::Fields::Unnamed(ref mut unnamed) => {
+            unnamed.unnamed = std::mem::take(&mut unnamed.unnamed)
+                .into_iter()
+                .filter(|f| !is_temp_field(f))
+                .collect();
+        }
+        syn::Fields::Unit => {}
+    }
+    for field in fields.iter_mut() {
+        strip_packetrs_attrs(&mut field.attrs);
+    }
+}
+
+fn generate_packetrs_read(ast: &DeriveInput) -> std::result::Result<TokenStream, syn::Error> {
     //println!("got ast: {:#?}", ast);
+    validate_param_values(ast)?;
     match ast.data {
         syn::Data::Struct(ref s) => {
-            let parsed = parse_struct(&ast.ident, &ast.attrs, s);
+            let parsed = parse_struct(&ast.ident, &ast.vis, &ast.attrs, s);
             //eprintln!("Parsed struct: {:#?}", parsed);
             Ok(generate_struct(&parsed)).map_err(|e: anyhow::Error| syn::Error::new_spanned(ast, e))
         }
         syn::Data::Enum(ref e) => {
-            let parsed = parse_enum(&ast.ident, &ast.attrs, e);
+            let parsed = parse_enum(&ast.ident, &ast.vis, &ast.attrs, e);
             //eprintln!("Parsed enum: {:#?}", parsed);
+            validate_enum_ids(&parsed)?;
             Ok(generate_enum(&parsed)).map_err(|e: anyhow::Error| syn::Error::new_spanned(ast, e))
         }
         _ => Err(syn::Error::new_spanned(
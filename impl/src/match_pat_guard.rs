@@ -9,6 +9,20 @@ pub(crate) struct MatchPatGuard {
     pub(crate) guard: Option<(syn::token::If, Box<syn::Expr>)>,
 }
 
+impl MatchPatGuard {
+    /// If this matches a single literal value (e.g. "0x01", but not "1 | 2", "1..=5" or
+    /// "x if x > 5"), return that literal.
+    pub(crate) fn get_literal(&self) -> Option<&syn::Lit> {
+        match (&self.pat, &self.guard) {
+            (syn::Pat::Lit(syn::PatLit { expr, .. }), None) => match expr.as_ref() {
+                syn::Expr::Lit(syn::ExprLit { lit, .. }) => Some(lit),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 impl syn::parse::Parse for MatchPatGuard {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(MatchPatGuard {
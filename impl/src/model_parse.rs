@@ -24,6 +24,7 @@ pub(crate) fn parse_field<'a, 'b>(field: &'a syn::Field, parent_params: &'b Vec<
 
 pub(crate) fn parse_struct<'a, 'b>(
     name: &'a syn::Ident,
+    vis: &'a syn::Visibility,
     attrs: &'a [syn::Attribute],
     struct_data: &'a syn::DataStruct,
 ) -> PacketRsStruct<'b>
@@ -41,6 +42,7 @@ where
 
     PacketRsStruct {
         name,
+        vis,
         fields,
         parameters,
     }
@@ -82,6 +84,7 @@ pub(crate) fn parse_variant<'a, 'b>(variant: &'a syn::Variant, parent_params: &'
 
 pub(crate) fn parse_enum<'a, 'b>(
     name: &'a syn::Ident,
+    vis: &'a syn::Visibility,
     attrs: &'a [syn::Attribute],
     enum_data: &'a syn::DataEnum,
 ) -> PacketRsEnum<'b>
@@ -99,6 +102,7 @@ where
 
     PacketRsEnum {
         name,
+        vis,
         parameters,
         variants,
     }
@@ -147,8 +151,22 @@ fn parse_packetrs_namevalue_param(nv: &syn::MetaNameValue) -> Option<PacketRsAtt
                 .unwrap_or_else(|e| panic!("Error parsing 'enum_key' value as expression: {}", e));
             Some(PacketRsAttributeParam::EnumKey(expr))
         }
+        "tag" => {
+            let ty = value_str
+                .parse::<syn::Type>()
+                .unwrap_or_else(|e| panic!("Error parsing 'tag' value as type: {}", e));
+            Some(PacketRsAttributeParam::EnumTag(ty))
+        }
+        "repr" => {
+            let ty = value_str
+                .parse::<syn::Type>()
+                .unwrap_or_else(|e| panic!("Error parsing 'repr' value as type: {}", e));
+            Some(PacketRsAttributeParam::Repr(ty))
+        }
         "id" => {
-            let id = syn::parse_str::<MatchPatGuard>(&value_str.value())
+            // Parsed from the LitStr itself so that errors about the id point at it
+            let id = value_str
+                .parse::<MatchPatGuard>()
                 .unwrap_or_else(|e| panic!("Error parsing 'id' value as MatchPatGuard: {}", e));
             Some(PacketRsAttributeParam::EnumId(id))
         }
@@ -158,14 +176,8 @@ fn parse_packetrs_namevalue_param(nv: &syn::MetaNameValue) -> Option<PacketRsAtt
                 .unwrap_or_else(|e| panic!("Error parsing 'assert' value as expression: {}", e));
             Some(PacketRsAttributeParam::Assert(expr))
         }
-        "byte_order" => {
-            match value_str.value().as_str() {
-                "big_endian" | "little_endian" | "network_order" => {
-                    Some(PacketRsAttributeParam::ByteOrder(value_str.clone()))
-                }
-                _ => panic!("Error: 'byte_order' parameter is invalid")
-            }
-        }
+        // Checked by validate_param_values
+        "byte_order" => Some(PacketRsAttributeParam::ByteOrder(value_str.clone())),
         "when" => {
             let expr = syn::parse_str::<syn::Expr>(&value_str.value())
                 .unwrap_or_else(|e| panic!("Error parsing 'when' value as expression: {}", e));
@@ -182,7 +194,86 @@ fn parse_packetrs_namevalue_param(nv: &syn::MetaNameValue) -> Option<PacketRsAtt
                 .unwrap_or_else(|e| panic!("Error parsing 'reader' param as a valid Ident: {}", e));
             Some(PacketRsAttributeParam::CustomReader(reader_ident))
         }
+        "read_as" => {
+            let ty = value_str
+                .parse::<syn::Type>()
+                .unwrap_or_else(|e| panic!("Error parsing 'read_as' value as type: {}", e));
+            Some(PacketRsAttributeParam::ReadAs(ty))
+        }
+        "map" => {
+            let expr = syn::parse_str::<syn::Expr>(&value_str.value())
+                .unwrap_or_else(|e| panic!("Error parsing 'map' value as expression: {}", e));
+            Some(PacketRsAttributeParam::Map(expr))
+        }
+        "try_map" => {
+            let expr = syn::parse_str::<syn::Expr>(&value_str.value())
+                .unwrap_or_else(|e| panic!("Error parsing 'try_map' value as expression: {}", e));
+            Some(PacketRsAttributeParam::TryMap(expr))
+        }
+        "default" => {
+            let expr = syn::parse_str::<syn::Expr>(&value_str.value())
+                .unwrap_or_else(|e| panic!("Error parsing 'default' value as expression: {}", e));
+            Some(PacketRsAttributeParam::Default(expr))
+        }
+        "validate" => {
+            let path = value_str
+                .parse::<syn::Path>()
+                .unwrap_or_else(|e| panic!("Error parsing 'validate' value as path: {}", e));
+            Some(PacketRsAttributeParam::Validate(path))
+        }
+        "pre_read" => {
+            let path = value_str
+                .parse::<syn::Path>()
+                .unwrap_or_else(|e| panic!("Error parsing 'pre_read' value as path: {}", e));
+            Some(PacketRsAttributeParam::PreRead(path))
+        }
+        "post_read" => {
+            let path = value_str
+                .parse::<syn::Path>()
+                .unwrap_or_else(|e| panic!("Error parsing 'post_read' value as path: {}", e));
+            Some(PacketRsAttributeParam::PostRead(path))
+        }
+        "peek_bits" => {
+            let expr = syn::parse_str::<syn::Expr>(&value_str.value())
+                .unwrap_or_else(|e| panic!("Error parsing 'peek_bits' value as expression: {}", e));
+            Some(PacketRsAttributeParam::PeekBits(expr))
+        }
+        "at" => {
+            let expr = syn::parse_str::<syn::Expr>(&value_str.value())
+                .unwrap_or_else(|e| panic!("Error parsing 'at' value as expression: {}", e));
+            Some(PacketRsAttributeParam::At(expr))
+        }
+        // Checked by validate_param_values
+        "seek_mode" => Some(PacketRsAttributeParam::SeekMode(value_str.clone())),
+        // Checked by validate_param_values
+        "string" => Some(PacketRsAttributeParam::StringEncoding(value_str.clone())),
+        // Checked by validate_param_values
+        "encoding" => Some(PacketRsAttributeParam::Encoding(value_str.clone())),
+        "bits" => {
+            let expr = value_str
+                .parse::<syn::Expr>()
+                .unwrap_or_else(|e| panic!("Error parsing 'bits' value as expression: {}", e));
+            Some(PacketRsAttributeParam::Bits(expr))
+        }
+        // Checked by validate_param_values
+        "bit_order" => Some(PacketRsAttributeParam::BitOrder(value_str.clone())),
+        "byte_len" => {
+            let expr = value_str
+                .parse::<syn::Expr>()
+                .unwrap_or_else(|e| panic!("Error parsing 'byte_len' value as expression: {}", e));
+            Some(PacketRsAttributeParam::ByteLen(expr))
+        }
+        "len_prefix" => {
+            let ty = value_str
+                .parse::<syn::Type>()
+                .unwrap_or_else(|e| panic!("Error parsing 'len_prefix' value as type: {}", e));
+            Some(PacketRsAttributeParam::LenPrefix(ty))
+        }
+        // Checked by validate_param_values
+        "on_duplicate" => Some(PacketRsAttributeParam::OnDuplicate(value_str.clone())),
         "ctx_delim" => Some(PacketRsAttributeParam::CtxDelim(value_str.clone())),
+        // Checked by validate_param_values
+        "time_format" => Some(PacketRsAttributeParam::TimeFormat(value_str.clone())),
         _ => {
             // TODO: refactor this to use a spanned compiler error
             panic!("Unrecognized packetrs attribute param name: {:?}", name)
@@ -190,13 +281,48 @@ fn parse_packetrs_namevalue_param(nv: &syn::MetaNameValue) -> Option<PacketRsAtt
     }
 }
 
+/// Parse a packetrs param that's just a name, without a value (i.e. a flag), e.g. 'skip'
+fn parse_packetrs_path_param(path: &syn::Path) -> Option<PacketRsAttributeParam> {
+    let name = path
+        .get_ident()
+        .unwrap_or_else(|| panic!("Couldn't get ident from Path: {:#?}", path));
+
+    match name.to_string().as_ref() {
+        "skip" => Some(PacketRsAttributeParam::Skip),
+        "temp" => Some(PacketRsAttributeParam::Temp),
+        "peek" => Some(PacketRsAttributeParam::Peek),
+        "seek_from_start" => Some(PacketRsAttributeParam::SeekFromStart),
+        "cstr" => Some(PacketRsAttributeParam::CStr),
+        _ => {
+            panic!("Unrecognized packetrs attribute flag name: {:?}", name)
+        }
+    }
+}
+
+/// Parse a packetrs param that's a name with a nested list of params, e.g. 'inner(count = "len")'
+fn parse_packetrs_list_param(list: &syn::MetaList) -> Option<PacketRsAttributeParam> {
+    let name = list
+        .path
+        .get_ident()
+        .unwrap_or_else(|| panic!("Couldn't get ident from Path: {:#?}", list.path));
+
+    match name.to_string().as_ref() {
+        "inner" => Some(PacketRsAttributeParam::Inner(
+            list.nested.iter().filter_map(parse_packetrs_param).collect(),
+        )),
+        _ => {
+            panic!("Unrecognized packetrs attribute list name: {:?}", name)
+        }
+    }
+}
+
 fn parse_packetrs_param(meta: &syn::NestedMeta) -> Option<PacketRsAttributeParam> {
     //eprintln!("parsing packetrs param: {:#?}", meta);
     if let syn::NestedMeta::Meta(ref m) = meta {
-        if let syn::Meta::NameValue(ref nv) = m {
-            parse_packetrs_namevalue_param(nv)
-        } else {
-            panic!("Packetrs attr param that wasn't a NameValue: {:?}", m);
+        match m {
+            syn::Meta::NameValue(ref nv) => parse_packetrs_namevalue_param(nv),
+            syn::Meta::Path(ref p) => parse_packetrs_path_param(p),
+            syn::Meta::List(ref l) => parse_packetrs_list_param(l),
         }
     } else {
         panic!(
@@ -209,7 +335,8 @@ fn parse_packetrs_param(meta: &syn::NestedMeta) -> Option<PacketRsAttributeParam
 /// Given a syn::Attribute that corresponds to a packetrs attribute, parse all
 /// the attribute params into PacketRsAttributeParam
 ///
-/// For now, assume all attrs are NameValue and anything else is invalid
+/// For now, assume all attrs are NameValue, Path (flags) or List ('inner') and anything else is
+/// invalid
 fn parse_packetrs_attrs(attr: &syn::Attribute) -> Vec<PacketRsAttributeParam> {
     if let Some(attr_ident) = attr.path.get_ident() {
         if attr_ident != "packetrs" {
@@ -239,7 +366,7 @@ fn parse_packetrs_attrs(attr: &syn::Attribute) -> Vec<PacketRsAttributeParam> {
     attr_params
 }
 
-fn parse_packetrs_attrs_from_attributes(attrs: &[syn::Attribute]) -> Vec<PacketRsAttributeParam> {
+pub(crate) fn parse_packetrs_attrs_from_attributes(attrs: &[syn::Attribute]) -> Vec<PacketRsAttributeParam> {
     if let Some(packetrs_attr) = get_attr("packetrs", attrs) {
         parse_packetrs_attrs(packetrs_attr)
     } else {
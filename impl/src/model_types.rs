@@ -7,14 +7,23 @@ pub(crate) enum PacketRsAttributeParam {
     // field should be parsed.
     Count(syn::Expr),
     // A String containing the list of context arguments that will be passed to the read method of
-    // the annotated field.  Value will be split by a comma by default.  See CtxDelim to override
-    // the delimiter.
+    // the annotated field.  Value is parsed as a comma-separated list of expressions (unless a
+    // CtxDelim is given).
     CallerContext(syn::LitStr),
     // A vector containing the list of function arguments in the form of syn::FnArg that is
     // required to be passed to the read method of the annotated struct.
     RequiredContext(Vec<syn::FnArg>),
     // A value containing the key to which enum variants should be mapped.  Tagged on the enum.
     EnumKey(syn::Expr),
+    // The type of a tag that an enum reads from the buffer itself (using the enum's byte order)
+    // before reading a variant.  The tag is available as a local named 'tag', and is the enum's
+    // key if no 'key' is given.  Tagged on the enum.
+    EnumTag(syn::Type),
+    // The unsigned integer type (native or from ux) that a fieldless enum is read as.  Each
+    // variant's 'id' (or discriminant) is the value of the type that corresponds to it, and a
+    // variant with a single unnamed field of the type and no id catches all other values.  Tagged
+    // on the enum.
+    Repr(syn::Type),
     // An ID of a specific enum variant that will be retrieved via the EnumKey.  Tagged on an enum
     // variant.
     EnumId(MatchPatGuard),
@@ -35,9 +44,82 @@ pub(crate) enum PacketRsAttributeParam {
     ReadValue(syn::Expr),
     // The name of a custom reader function to be used to read this type
     CustomReader(syn::Ident),
-    // Sometimes values including a comma need to be passed in a CallerContext argument.  If so,
-    // the default delimiter can be overriden via this parameter.
+    // A delimiter on which to split a CallerContext argument instead of parsing it as a list of
+    // expressions.  No longer needed (commas within expressions are handled), but still accepted
+    // for compatibility.
     CtxDelim(syn::LitStr),
+    // A string describing the wire format a time field (SystemTime or Duration) should be read
+    // from.  Supported values: "ntp64", "ntp32"
+    TimeFormat(syn::LitStr),
+    // The type that should be read from the buffer for this field, when it differs from the type
+    // of the field itself.  Used in combination with 'map' or 'try_map'.
+    ReadAs(syn::Type),
+    // An expression (e.g. a closure) that converts the read value into the field's value.
+    Map(syn::Expr),
+    // Like 'Map', but the expression returns a Result.  An error is propagated as a read error
+    // of the field.
+    TryMap(syn::Expr),
+    // A flag denoting that the field isn't read from the buffer at all, and is instead set to its
+    // Default::default() value.
+    Skip,
+    // An expression that gives the value of a field that isn't read from the buffer.  When used
+    // with 'when', it's the value used when the 'when' expression is false.
+    Default(syn::Expr),
+    // A flag denoting that the field is read into a local (so it can be used in the expressions of
+    // later fields) but isn't a field of the type itself.  Requires packetrs_derive, which removes
+    // the field from the type's definition.
+    Temp,
+    // A flag denoting that the field's value is read without advancing the cursor: its position is
+    // restored after the read, so later fields (e.g. an enum variant) read the same data again.
+    // Tagged on a field, or on an enum with a 'tag' to peek the tag.
+    Peek,
+    // Like 'Peek', but the value is the given number of bits, which are read into the field's type
+    // (which may be wider than that number of bits).
+    PeekBits(syn::Expr),
+    // An expression giving the offset (in bytes) at which the field is read, relative to the start
+    // of the struct or enum it belongs to (or to the start of the buffer, with 'SeekFromStart').
+    At(syn::Expr),
+    // A flag denoting that an 'At' offset is relative to the start of the buffer.
+    SeekFromStart,
+    // What happens to the cursor after reading a field at an 'At' offset: "restore" (the default)
+    // moves it back to where it was before the field, and "advance" leaves it after the field.
+    SeekMode(syn::LitStr),
+    // What happens when a map field (e.g. a HashMap) reads a key that it already contains: "error"
+    // (the default) fails the read, "first" keeps the value that was read first, and "last"
+    // replaces it with the later value.
+    OnDuplicate(syn::LitStr),
+    // The encoding a string field (String or Cow<str>) is read with: "utf8", "ascii" or "latin1".
+    // Requires exactly one of 'ByteLen', 'CStr' or 'LenPrefix', which determine its length.
+    StringEncoding(syn::LitStr),
+    // An expression giving the length of a string field, in bytes
+    ByteLen(syn::Expr),
+    // A flag denoting that a string field is terminated by a null byte (which isn't part of the
+    // string)
+    CStr,
+    // The type of a length (in bytes) that's read before a string field
+    LenPrefix(syn::Type),
+    // The variable-length encoding an integer field is read with: "quic_varint", "uleb128",
+    // "sleb128" or "zigzag".  The value is decoded as a u64 (or i64) and converted into the field's
+    // type.
+    Encoding(syn::LitStr),
+    // An expression giving the number of bits in a bitmap field (a BitVec, a Vec<bool> or anything
+    // else that can be collected from bools)
+    Bits(syn::Expr),
+    // The order of the bits in a bitmap field: "msb" (the default) or "lsb"
+    BitOrder(syn::LitStr),
+    // The params of the elements of a collection, or of the value of an Option, given as a nested
+    // list (e.g. 'inner(count = "length")' for a Vec<Vec<u8>>).  Can itself contain an 'inner', for
+    // deeper nesting.
+    Inner(Vec<PacketRsAttributeParam>),
+    // The path of a function that's passed a reference to the value after it's been read, and
+    // returns a Result denoting whether or not it's valid.  Tagged on a struct or enum.
+    Validate(syn::Path),
+    // The path of a function that's passed the buffer before anything is read.  Tagged on a struct
+    // or enum.
+    PreRead(syn::Path),
+    // The path of a function that's passed a mutable reference to the value and the buffer after
+    // the value has been read (and before it's validated).  Tagged on a struct or enum.
+    PostRead(syn::Path),
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +132,7 @@ pub(crate) struct PacketRsField<'a> {
 #[derive(Debug)]
 pub(crate) struct PacketRsStruct<'a> {
     pub name: &'a syn::Ident,
+    pub vis: &'a syn::Visibility,
     pub fields: Vec<PacketRsField<'a>>,
     pub parameters: Vec<PacketRsAttributeParam>,
 }
@@ -64,6 +147,7 @@ pub(crate) struct PacketRsEnumVariant<'a> {
 #[derive(Debug)]
 pub(crate) struct PacketRsEnum<'a> {
     pub name: &'a syn::Ident,
+    pub vis: &'a syn::Visibility,
     pub parameters: Vec<PacketRsAttributeParam>,
     pub variants: Vec<PacketRsEnumVariant<'a>>,
 }
@@ -119,6 +203,17 @@ macro_rules! get_params {
     };
 }
 
+/// Return true if $params contains the given variant.  Works with unit variants (i.e. flags), which
+/// get_param can't be used for.
+#[macro_export]
+macro_rules! has_param {
+    ($params:expr, $variant:tt) => {
+        $params
+            .iter()
+            .any(|p| matches!(p, PacketRsAttributeParam::$variant))
+    };
+}
+
 pub(crate) fn are_fields_named(fields: &[PacketRsField<'_>]) -> bool {
     fields.iter().any(|f| f.name.is_some())
 }
@@ -0,0 +1,114 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts, byte_order::ByteOrder};
+
+use crate::{error::PacketRsResult, packetrs_read::PacketrsRead};
+
+/// The number of seconds between the NTP epoch (1900-01-01) and the UNIX epoch (1970-01-01)
+const NTP_UNIX_EPOCH_OFFSET_SECS: u64 = 2_208_988_800;
+
+/// A 64-bit NTP timestamp: 32 bits of seconds since the NTP epoch followed by 32 bits of
+/// fractional seconds.
+///
+/// Can be read directly, or a `SystemTime`/`Duration` field can be read from one via
+/// `#[packetrs(time_format = "ntp64")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NtpTimestamp {
+    pub seconds: u32,
+    pub fraction: u32,
+}
+
+/// The "middle" 32 bits of an NTP timestamp (as used by e.g. RTCP's LSR and DLSR fields): 16 bits
+/// of seconds followed by 16 bits of fractional seconds.
+///
+/// Can be read directly, or a `Duration` field can be read from one via
+/// `#[packetrs(time_format = "ntp32")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NtpShortTimestamp {
+    pub seconds: u16,
+    pub fraction: u16,
+}
+
+impl PacketrsRead<()> for NtpTimestamp {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, _: ()) -> PacketRsResult<Self> {
+        Ok(NtpTimestamp {
+            seconds: buf.read_u32::<T>()?,
+            fraction: buf.read_u32::<T>()?,
+        })
+    }
+}
+
+impl PacketrsRead<()> for NtpShortTimestamp {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, _: ()) -> PacketRsResult<Self> {
+        Ok(NtpShortTimestamp {
+            seconds: buf.read_u16::<T>()?,
+            fraction: buf.read_u16::<T>()?,
+        })
+    }
+}
+
+/// The time elapsed since the NTP epoch
+impl From<NtpTimestamp> for Duration {
+    fn from(ts: NtpTimestamp) -> Self {
+        let nanos = (ts.fraction as u64 * 1_000_000_000) >> 32;
+        Duration::new(ts.seconds as u64, nanos as u32)
+    }
+}
+
+/// NOTE: NTP era rollover (in 2036) isn't handled: all timestamps are treated as being in era 0.
+impl From<NtpTimestamp> for SystemTime {
+    fn from(ts: NtpTimestamp) -> Self {
+        let since_ntp_epoch = Duration::from(ts);
+        let epoch_offset = Duration::from_secs(NTP_UNIX_EPOCH_OFFSET_SECS);
+        if since_ntp_epoch >= epoch_offset {
+            UNIX_EPOCH + (since_ntp_epoch - epoch_offset)
+        } else {
+            UNIX_EPOCH - (epoch_offset - since_ntp_epoch)
+        }
+    }
+}
+
+impl From<NtpShortTimestamp> for Duration {
+    fn from(ts: NtpShortTimestamp) -> Self {
+        let nanos = (ts.fraction as u64 * 1_000_000_000) >> 16;
+        Duration::new(ts.seconds as u64, nanos as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ntp_timestamp_to_system_time() {
+        let ts = NtpTimestamp {
+            seconds: (NTP_UNIX_EPOCH_OFFSET_SECS + 10) as u32,
+            fraction: 0x8000_0000,
+        };
+        assert_eq!(
+            SystemTime::from(ts),
+            UNIX_EPOCH + Duration::from_millis(10_500)
+        );
+    }
+
+    #[test]
+    fn test_ntp_timestamp_before_unix_epoch() {
+        let ts = NtpTimestamp {
+            seconds: 0,
+            fraction: 0,
+        };
+        assert_eq!(
+            SystemTime::from(ts),
+            UNIX_EPOCH - Duration::from_secs(NTP_UNIX_EPOCH_OFFSET_SECS)
+        );
+    }
+
+    #[test]
+    fn test_ntp_short_timestamp_to_duration() {
+        let ts = NtpShortTimestamp {
+            seconds: 3,
+            fraction: 0x4000,
+        };
+        assert_eq!(Duration::from(ts), Duration::from_millis(3_250));
+    }
+}
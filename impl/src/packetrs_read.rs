@@ -1,13 +1,126 @@
-use b3::{bit_cursor::BitCursor, ux::*, bit_read_exts::BitReadExts, byte_order::ByteOrder};
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    rc::Rc,
+    sync::Arc,
+};
 
-use crate::error::PacketRsResult;
+use anyhow::bail;
+use b3::{bit_cursor::BitCursor, ux::*, bit_read_exts::BitReadExts, byte_order::{ByteOrder, NetworkOrder}};
+
+use crate::{error::PacketRsResult, read_options::ReadOptions};
 
 /// This trait is what will be derived for a struct, and can be used to implement custom read logic
 /// for types
 pub trait PacketrsRead<Ctx>: Sized {
     fn read<T: ByteOrder>(buf: &mut BitCursor, ctx: Ctx) -> PacketRsResult<Self>;
+
+    /// Read while enforcing the limits set in the given ReadOptions.  Derived impls enforce the
+    /// limits and pass them down to the reads of their fields.  The default implementation ignores
+    /// them, which is fine for types whose reads don't allocate or recurse.
+    fn read_with_options<T: ByteOrder>(
+        buf: &mut BitCursor,
+        ctx: Ctx,
+        _read_options: &mut ReadOptions,
+    ) -> PacketRsResult<Self> {
+        Self::read::<T>(buf, ctx)
+    }
+
+    /// Read a value from the given bytes (in network order), returning an error if the entire
+    /// buffer wasn't consumed.
+    fn from_bytes_with_ctx(data: &[u8], ctx: Ctx) -> PacketRsResult<Self> {
+        Self::from_bytes_with_ctx_and_options(data, ctx, &mut ReadOptions::default())
+    }
+
+    /// Like [`PacketrsRead::from_bytes_with_ctx`], but enforcing the limits set in the given
+    /// ReadOptions.
+    fn from_bytes_with_ctx_and_options(
+        data: &[u8],
+        ctx: Ctx,
+        read_options: &mut ReadOptions,
+    ) -> PacketRsResult<Self> {
+        let mut buf = BitCursor::from_vec(data.to_vec());
+        let value = Self::read_with_options::<NetworkOrder>(&mut buf, ctx, read_options)?;
+        if buf.bits_remaining() != 0 {
+            bail!(
+                "{} trailing bits left after reading {} bytes",
+                buf.bits_remaining(),
+                data.len()
+            );
+        }
+        Ok(value)
+    }
+
+    /// Read a value from the given bytes (in network order), returning it along with whatever
+    /// bytes weren't consumed.  Returns an error if the read didn't end on a byte boundary.
+    fn from_bytes_partial_with_ctx(data: &[u8], ctx: Ctx) -> PacketRsResult<(Self, &[u8])> {
+        Self::from_bytes_partial_with_ctx_and_options(data, ctx, &mut ReadOptions::default())
+    }
+
+    /// Like [`PacketrsRead::from_bytes_partial_with_ctx`], but enforcing the limits set in the
+    /// given ReadOptions.
+    fn from_bytes_partial_with_ctx_and_options<'a>(
+        data: &'a [u8],
+        ctx: Ctx,
+        read_options: &mut ReadOptions,
+    ) -> PacketRsResult<(Self, &'a [u8])> {
+        let mut buf = BitCursor::from_vec(data.to_vec());
+        let value = Self::read_with_options::<NetworkOrder>(&mut buf, ctx, read_options)?;
+        let bits_consumed = data.len() * 8 - buf.bits_remaining();
+        // Not usize::is_multiple_of, which would need Rust 1.87
+        let partial_byte_bits = bits_consumed % 8;
+        if partial_byte_bits != 0 {
+            bail!("Read ended in the middle of a byte (after {} bits)", bits_consumed);
+        }
+        Ok((value, &data[bits_consumed / 8..]))
+    }
+}
+
+/// Derived for types with 'required_ctx', so that callers can pass the context by name (e.g.
+/// `ctx = "length = attr_len, message_type = attr_type"`) rather than by position.  `Named` is a
+/// struct with a field for each required context value, so the compiler checks that each of them
+/// is passed exactly once.  The lifetime is that of any references in the context (e.g.
+/// `required_ctx = "hdr: &RtpHeader"`).
+pub trait PacketrsNamedCtx<'ctx> {
+    /// The context type of the PacketrsRead impl
+    type Ctx;
+    /// The struct with a field for each value in the context
+    type Named;
+
+    fn into_ctx(named: Self::Named) -> Self::Ctx;
 }
 
+/// Convenience methods for reading types which don't require any context directly from bytes.
+/// See [`PacketrsRead::from_bytes_with_ctx`] and [`PacketrsRead::from_bytes_partial_with_ctx`].
+pub trait PacketrsReadExt: PacketrsRead<()> {
+    /// Read a value from the given bytes (in network order), returning an error if the entire
+    /// buffer wasn't consumed.
+    fn from_bytes(data: &[u8]) -> PacketRsResult<Self> {
+        <Self as PacketrsRead<()>>::from_bytes_with_ctx(data, ())
+    }
+
+    /// Like [`PacketrsReadExt::from_bytes`], but enforcing the limits set in the given ReadOptions.
+    fn from_bytes_with_options(data: &[u8], read_options: &mut ReadOptions) -> PacketRsResult<Self> {
+        <Self as PacketrsRead<()>>::from_bytes_with_ctx_and_options(data, (), read_options)
+    }
+
+    /// Read a value from the given bytes (in network order), returning it along with whatever
+    /// bytes weren't consumed.
+    fn from_bytes_partial(data: &[u8]) -> PacketRsResult<(Self, &[u8])> {
+        <Self as PacketrsRead<()>>::from_bytes_partial_with_ctx(data, ())
+    }
+
+    /// Like [`PacketrsReadExt::from_bytes_partial`], but enforcing the limits set in the given
+    /// ReadOptions.
+    fn from_bytes_partial_with_options<'a>(
+        data: &'a [u8],
+        read_options: &mut ReadOptions,
+    ) -> PacketRsResult<(Self, &'a [u8])> {
+        <Self as PacketrsRead<()>>::from_bytes_partial_with_ctx_and_options(data, (), read_options)
+    }
+}
+
+impl<T: PacketrsRead<()>> PacketrsReadExt for T {}
+
 macro_rules! packetrs_read_builtin {
     ($type:ty) => {
         impl PacketrsRead<()> for $type {
@@ -70,3 +183,102 @@ packetrs_read_builtin_bo!(u29);
 packetrs_read_builtin_bo!(u30);
 packetrs_read_builtin_bo!(u31);
 packetrs_read_builtin_bo!(u32);
+
+/// IPv4 and IPv6 addresses are always carried in network order, so the `ByteOrder` parameter is
+/// ignored for them.
+impl PacketrsRead<()> for Ipv4Addr {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, _: ()) -> PacketRsResult<Self> {
+        Ok(Ipv4Addr::from(<[u8; 4]>::read::<T>(buf, ())?))
+    }
+}
+
+impl PacketrsRead<()> for Ipv6Addr {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, _: ()) -> PacketRsResult<Self> {
+        Ok(Ipv6Addr::from(<[u8; 16]>::read::<T>(buf, ())?))
+    }
+}
+
+/// Fixed-size arrays, e.g. a `[u8; 6]` MAC address, are read element by element.  The context is
+/// passed to the read of each element.
+impl<Ctx: Clone, V: PacketrsRead<Ctx>, const N: usize> PacketrsRead<Ctx> for [V; N] {
+    fn read<T: ByteOrder>(buf: &mut BitCursor, ctx: Ctx) -> PacketRsResult<Self> {
+        Self::read_with_options::<T>(buf, ctx, &mut ReadOptions::default())
+    }
+
+    fn read_with_options<T: ByteOrder>(
+        buf: &mut BitCursor,
+        ctx: Ctx,
+        read_options: &mut ReadOptions,
+    ) -> PacketRsResult<Self> {
+        let mut values = Vec::with_capacity(N);
+        for _ in 0..N {
+            values.push(V::read_with_options::<T>(buf, ctx.clone(), read_options)?);
+        }
+        match values.try_into() {
+            Ok(values) => Ok(values),
+            Err(_) => unreachable!("exactly {} values were read", N),
+        }
+    }
+}
+
+/// Tuples are read element by element, in order.  None of the elements can require context.
+macro_rules! packetrs_read_tuple {
+    ($($element:ident),+) => {
+        impl<$($element: PacketrsRead<()>),+> PacketrsRead<()> for ($($element,)+) {
+            fn read<T: ByteOrder>(buf: &mut BitCursor, _: ()) -> PacketRsResult<Self> {
+                Self::read_with_options::<T>(buf, (), &mut ReadOptions::default())
+            }
+
+            fn read_with_options<T: ByteOrder>(
+                buf: &mut BitCursor,
+                _: (),
+                read_options: &mut ReadOptions,
+            ) -> PacketRsResult<Self> {
+                Ok(($($element::read_with_options::<T>(buf, (), read_options)?,)+))
+            }
+        }
+    };
+}
+
+packetrs_read_tuple!(A);
+packetrs_read_tuple!(A, B);
+packetrs_read_tuple!(A, B, C);
+packetrs_read_tuple!(A, B, C, D);
+packetrs_read_tuple!(A, B, C, D, E);
+packetrs_read_tuple!(A, B, C, D, E, F);
+packetrs_read_tuple!(A, B, C, D, E, F, G);
+packetrs_read_tuple!(A, B, C, D, E, F, G, H);
+
+/// Pointer types are read as the type they point to, which makes recursive types (e.g. a TLV whose
+/// value can contain other TLVs) possible.  The read options are passed through, so their depth
+/// limit applies to the recursion.
+macro_rules! packetrs_read_pointer {
+    ($pointer:ident) => {
+        impl<Ctx, V: PacketrsRead<Ctx>> PacketrsRead<Ctx> for $pointer<V> {
+            fn read<T: ByteOrder>(buf: &mut BitCursor, ctx: Ctx) -> PacketRsResult<Self> {
+                V::read::<T>(buf, ctx).map($pointer::new)
+            }
+
+            fn read_with_options<T: ByteOrder>(
+                buf: &mut BitCursor,
+                ctx: Ctx,
+                read_options: &mut ReadOptions,
+            ) -> PacketRsResult<Self> {
+                V::read_with_options::<T>(buf, ctx, read_options).map($pointer::new)
+            }
+        }
+
+        impl<'ctx, V: PacketrsNamedCtx<'ctx>> PacketrsNamedCtx<'ctx> for $pointer<V> {
+            type Ctx = V::Ctx;
+            type Named = V::Named;
+
+            fn into_ctx(named: Self::Named) -> Self::Ctx {
+                V::into_ctx(named)
+            }
+        }
+    };
+}
+
+packetrs_read_pointer!(Box);
+packetrs_read_pointer!(Rc);
+packetrs_read_pointer!(Arc);
@@ -0,0 +1,197 @@
+use crate::error::{LimitExceeded, PacketRsResult};
+
+/// The maximum depth to which derived types can be nested, unless another limit is set.  This
+/// stops malicious input from overflowing the stack when reading recursive types (e.g. a `Box`
+/// field whose type contains itself).
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Limits which are enforced while reading, to protect against malicious or malformed input
+/// (e.g. a huge 'count' value read from the buffer, or deeply nested recursive types).  Only the
+/// depth is limited by default (to [`DEFAULT_MAX_DEPTH`]).
+///
+/// Hitting a limit results in a [`LimitExceeded`] error.
+///
+/// ```ignore
+/// let mut opts = ReadOptions::new()
+///     .max_collection_len(1500)
+///     .max_total_alloc(64 * 1024)
+///     .max_depth(16);
+/// let packet = StunPacket::read_with_options::<NetworkOrder>(&mut buf, (), &mut opts)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    max_collection_len: Option<usize>,
+    max_total_alloc: Option<usize>,
+    max_depth: Option<usize>,
+    max_while_iterations: Option<usize>,
+    // The current nesting depth
+    depth: usize,
+    // The total number of bytes allocated for collection elements so far
+    allocated: usize,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            max_collection_len: None,
+            max_total_alloc: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_while_iterations: None,
+            depth: 0,
+            allocated: 0,
+        }
+    }
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of elements that can be read into a single collection
+    pub fn max_collection_len(mut self, max: usize) -> Self {
+        self.max_collection_len = Some(max);
+        self
+    }
+
+    /// Set the maximum total number of bytes that can be allocated for collection elements
+    /// across the entire read
+    pub fn max_total_alloc(mut self, max: usize) -> Self {
+        self.max_total_alloc = Some(max);
+        self
+    }
+
+    /// Set the maximum depth to which derived types can be nested
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// Remove the depth limit, which is only safe when the types being read aren't recursive
+    pub fn unlimited_depth(mut self) -> Self {
+        self.max_depth = None;
+        self
+    }
+
+    /// Set the maximum number of iterations of a single 'while' loop
+    pub fn max_while_iterations(mut self, max: usize) -> Self {
+        self.max_while_iterations = Some(max);
+        self
+    }
+
+    /// Called by generated code when starting to read a type
+    #[doc(hidden)]
+    pub fn enter(&mut self) -> PacketRsResult<()> {
+        if let Some(limit) = self.max_depth {
+            if self.depth >= limit {
+                return Err(LimitExceeded::Depth { limit }.into());
+            }
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Called by generated code when done reading a type (whether or not it succeeded)
+    #[doc(hidden)]
+    pub fn exit(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Called by generated code before reading a collection of the given length
+    #[doc(hidden)]
+    pub fn check_collection_len(&self, len: usize) -> PacketRsResult<()> {
+        match self.max_collection_len {
+            Some(limit) if len > limit => Err(LimitExceeded::CollectionLength {
+                limit,
+                requested: len,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Called by generated code before allocating the given number of bytes for collection
+    /// elements
+    #[doc(hidden)]
+    pub fn allocate(&mut self, bytes: usize) -> PacketRsResult<()> {
+        let requested = self.allocated.saturating_add(bytes);
+        match self.max_total_alloc {
+            Some(limit) if requested > limit => {
+                Err(LimitExceeded::TotalAllocation { limit, requested }.into())
+            }
+            _ => {
+                self.allocated = requested;
+                Ok(())
+            }
+        }
+    }
+
+    /// Called by generated code before each iteration of a 'while' loop, with the number of
+    /// iterations completed so far
+    #[doc(hidden)]
+    pub fn check_while_iterations(&self, iterations: usize) -> PacketRsResult<()> {
+        match self.max_while_iterations {
+            Some(limit) if iterations >= limit => {
+                Err(LimitExceeded::WhileIterations { limit }.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_limits_by_default() {
+        let mut opts = ReadOptions::default();
+        assert!(opts.enter().is_ok());
+        assert!(opts.check_collection_len(usize::MAX).is_ok());
+        assert!(opts.allocate(usize::MAX).is_ok());
+        assert!(opts.check_while_iterations(usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_default_max_depth() {
+        let mut opts = ReadOptions::default();
+        for _ in 0..DEFAULT_MAX_DEPTH {
+            assert!(opts.enter().is_ok());
+        }
+        assert!(opts.enter().is_err());
+
+        let mut opts = ReadOptions::new().unlimited_depth();
+        for _ in 0..=DEFAULT_MAX_DEPTH {
+            assert!(opts.enter().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut opts = ReadOptions::new().max_depth(2);
+        assert!(opts.enter().is_ok());
+        assert!(opts.enter().is_ok());
+        let err = opts.enter().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LimitExceeded>(),
+            Some(&LimitExceeded::Depth { limit: 2 })
+        );
+        opts.exit();
+        assert!(opts.enter().is_ok());
+    }
+
+    #[test]
+    fn test_max_total_alloc() {
+        let mut opts = ReadOptions::new().max_total_alloc(10);
+        assert!(opts.allocate(6).is_ok());
+        let err = opts.allocate(6).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LimitExceeded>(),
+            Some(&LimitExceeded::TotalAllocation {
+                limit: 10,
+                requested: 12
+            })
+        );
+        assert!(opts.allocate(4).is_ok());
+    }
+}
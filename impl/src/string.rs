@@ -0,0 +1,106 @@
+use anyhow::{anyhow, bail};
+use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts};
+
+use crate::{error::PacketRsResult, read_options::ReadOptions};
+
+/// The encodings that a string field can be read with, via `#[packetrs(string = "...")]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding {
+    /// "utf8": the bytes must be valid UTF-8
+    Utf8,
+    /// "ascii": every byte must be 7-bit ASCII
+    Ascii,
+    /// "latin1": every byte is a character (ISO 8859-1), so any bytes are valid
+    Latin1,
+}
+
+/// Decode the given bytes as a string of the given encoding.  Errors describe where the invalid
+/// data is: the field being read is added as context by the generated code.
+pub fn decode_string(bytes: Vec<u8>, encoding: StringEncoding) -> PacketRsResult<String> {
+    match encoding {
+        StringEncoding::Utf8 => {
+            String::from_utf8(bytes).map_err(|e| anyhow!("Invalid UTF-8 string: {}", e))
+        }
+        StringEncoding::Ascii => {
+            if let Some(position) = bytes.iter().position(|b| !b.is_ascii()) {
+                bail!(
+                    "Invalid ASCII byte {:#x} at byte {}",
+                    bytes[position],
+                    position
+                );
+            }
+            // ASCII is a subset of UTF-8
+            Ok(String::from_utf8(bytes)?)
+        }
+        StringEncoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+    }
+}
+
+/// Read the given number of bytes of a string.  Called by generated code.
+#[doc(hidden)]
+pub fn read_string_bytes(
+    buf: &mut BitCursor,
+    len: usize,
+    read_options: &mut ReadOptions,
+) -> PacketRsResult<Vec<u8>> {
+    read_options.check_collection_len(len)?;
+    read_options.allocate(len)?;
+    let mut bytes = Vec::with_capacity(len);
+    for _ in 0..len {
+        bytes.push(buf.read_u8()?);
+    }
+    Ok(bytes)
+}
+
+/// Read the bytes of a null-terminated string.  The terminator is consumed, but isn't included in
+/// the returned bytes.  Called by generated code.
+#[doc(hidden)]
+pub fn read_cstr_bytes(
+    buf: &mut BitCursor,
+    read_options: &mut ReadOptions,
+) -> PacketRsResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = buf.read_u8()?;
+        if byte == 0 {
+            return Ok(bytes);
+        }
+        read_options.check_collection_len(bytes.len() + 1)?;
+        read_options.allocate(1)?;
+        bytes.push(byte);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_string() {
+        assert_eq!(
+            decode_string("héllo".as_bytes().to_vec(), StringEncoding::Utf8).unwrap(),
+            "héllo"
+        );
+        assert!(decode_string(vec![b'a', 0xff], StringEncoding::Utf8).is_err());
+        assert_eq!(
+            decode_string(b"GET".to_vec(), StringEncoding::Ascii).unwrap(),
+            "GET"
+        );
+        assert!(decode_string(vec![b'a', 0xe9], StringEncoding::Ascii).is_err());
+        assert_eq!(
+            decode_string(vec![b'h', 0xe9], StringEncoding::Latin1).unwrap(),
+            "hé"
+        );
+    }
+
+    #[test]
+    fn test_read_cstr_bytes() {
+        let mut buf = BitCursor::from_vec(vec![b'h', b'i', 0, b'!']);
+        let bytes = read_cstr_bytes(&mut buf, &mut ReadOptions::default()).unwrap();
+        assert_eq!(bytes, b"hi");
+        assert_eq!(buf.read_u8().unwrap(), b'!');
+
+        let mut buf = BitCursor::from_vec(vec![b'h', b'i']);
+        assert!(read_cstr_bytes(&mut buf, &mut ReadOptions::default()).is_err());
+    }
+}
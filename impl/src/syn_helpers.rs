@@ -1,4 +1,130 @@
-use quote::quote;
+use quote::{format_ident, quote};
+use syn::{
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    visit_mut::{self, VisitMut},
+    Token,
+};
+
+/// The lifetime given to references in a 'required_ctx' whose lifetime is elided, e.g.
+/// "hdr: &RtpHeader"
+pub(crate) fn ctx_lifetime() -> syn::Lifetime {
+    syn::Lifetime::new("'packetrs_ctx", proc_macro2::Span::call_site())
+}
+
+/// Replaces elided (and '_) lifetimes with the context lifetime
+struct AssignCtxLifetime;
+
+impl VisitMut for AssignCtxLifetime {
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(ctx_lifetime());
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = ctx_lifetime();
+        }
+    }
+}
+
+/// Collects all of the distinct lifetimes used in a type
+#[derive(Default)]
+struct CollectLifetimes {
+    lifetimes: Vec<syn::Lifetime>,
+}
+
+impl<'ast> Visit<'ast> for CollectLifetimes {
+    fn visit_lifetime(&mut self, lifetime: &'ast syn::Lifetime) {
+        if lifetime.ident != "static" && !self.lifetimes.contains(lifetime) {
+            self.lifetimes.push(lifetime.clone());
+        }
+        visit::visit_lifetime(self, lifetime);
+    }
+}
+
+/// Return all of the distinct (non-'static) lifetimes used in the given type, in order of
+/// appearance.
+pub(crate) fn get_lifetimes(ty: &syn::Type) -> Vec<syn::Lifetime> {
+    let mut collector = CollectLifetimes::default();
+    collector.visit_type(ty);
+    collector.lifetimes
+}
+
+/// Replaces the untyped integer literals that a variable is compared against (e.g. the 0x1000 in
+/// "v < 0x1000") with values of the given ux type constructed from them
+struct CoerceComparedLiterals<'a> {
+    var_name: &'a syn::Ident,
+    ty: &'a syn::Type,
+}
+
+impl CoerceComparedLiterals<'_> {
+    fn is_var(&self, expr: &syn::Expr) -> bool {
+        matches!(expr, syn::Expr::Path(p) if p.path.is_ident(self.var_name))
+    }
+
+    fn coerce(&self, expr: &mut syn::Expr) {
+        if let syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(ref lit_int),
+            ..
+        }) = expr
+        {
+            if lit_int.suffix().is_empty() {
+                let ty = self.ty;
+                *expr = syn::parse_quote! { <#ty>::new(#lit_int) };
+            }
+        }
+    }
+}
+
+impl VisitMut for CoerceComparedLiterals<'_> {
+    fn visit_expr_binary_mut(&mut self, binary: &mut syn::ExprBinary) {
+        visit_mut::visit_expr_binary_mut(self, binary);
+        if !matches!(
+            binary.op,
+            syn::BinOp::Eq(_)
+                | syn::BinOp::Ne(_)
+                | syn::BinOp::Lt(_)
+                | syn::BinOp::Le(_)
+                | syn::BinOp::Gt(_)
+                | syn::BinOp::Ge(_)
+        ) {
+            return;
+        }
+        if self.is_var(&binary.left) {
+            self.coerce(&mut binary.right);
+        } else if self.is_var(&binary.right) {
+            self.coerce(&mut binary.left);
+        }
+    }
+}
+
+/// If the given expression is a closure with a single argument (e.g. "|v| v < 0x1000"), coerce the
+/// untyped integer literals that the argument is directly compared against into the given ux type,
+/// so that a ux value can be passed to it.  Anything else is returned as is.
+pub(crate) fn coerce_compared_literals(expr: &syn::Expr, ty: &syn::Type) -> syn::Expr {
+    let mut expr = expr.clone();
+    if let syn::Expr::Closure(ref mut closure) = expr {
+        let var_name = match closure.inputs.first() {
+            Some(syn::Pat::Ident(pat_ident)) => Some(pat_ident.ident.clone()),
+            Some(syn::Pat::Type(pat_type)) => match *pat_type.pat {
+                syn::Pat::Ident(ref pat_ident) => Some(pat_ident.ident.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let (Some(var_name), 1) = (var_name, closure.inputs.len()) {
+            CoerceComparedLiterals {
+                var_name: &var_name,
+                ty,
+            }
+            .visit_expr_mut(&mut closure.body);
+        }
+    }
+    expr
+}
 
 /// Parse a String that looks like this:
 /// "arg_one: type_one, arg_two: type_two, ...
@@ -7,11 +133,27 @@ pub(crate) fn parse_fn_args_from_lit_str(
     req_ctx: &syn::LitStr,
 ) -> Result<Vec<syn::FnArg>, syn::Error> {
     req_ctx
-        .value()
-        .split(',')
-        .map(|s| syn::LitStr::new(s, req_ctx.span()))
-        .map(|ls| ls.parse::<syn::FnArg>())
-        .collect::<Result<Vec<syn::FnArg>, syn::Error>>()
+        .parse_with(Punctuated::<syn::FnArg, Token![,]>::parse_terminated)
+        .map(|args| args.into_iter().collect())
+}
+
+/// Parse the given LitStr as a comma-separated list of expressions, e.g. the value of a 'ctx'
+/// attribute.  Commas nested within an expression (e.g. "f(a, b)") don't split it.
+pub(crate) fn parse_exprs_from_lit_str(ctx: &syn::LitStr) -> Result<Vec<syn::Expr>, syn::Error> {
+    ctx.parse_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)
+        .map(|exprs| exprs.into_iter().collect())
+}
+
+/// If the given 'ctx' expression is a named argument (e.g. "length = attr_len"), return its name and
+/// value.
+pub(crate) fn get_named_ctx_arg(expr: &syn::Expr) -> Option<(&syn::Ident, &syn::Expr)> {
+    match expr {
+        syn::Expr::Assign(syn::ExprAssign { left, right, .. }) => match **left {
+            syn::Expr::Path(ref p) => p.path.get_ident().map(|name| (name, &**right)),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 /// Find and return the attribute that matches the given name from the given attribute vector, if
@@ -70,16 +212,22 @@ pub(crate) fn get_var_name_from_fn_arg(fn_arg: &syn::FnArg) -> Option<&syn::Iden
     }
 }
 
-fn get_type_ident(ty: &syn::Type) -> Option<&syn::Ident> {
-    let type_path = match ty {
-        syn::Type::Path(p) => p,
-        _ => {
-            // todo: return result with this error instead
-            eprintln!("Unsupported type: {:?}", ty);
-            return None;
-        }
-    };
-    type_path.path.get_ident()
+/// Return the type of each of the given FnArgs parsed from a 'required_ctx' attribute, with any
+/// elided lifetimes replaced by the context lifetime (so the types can be used in an impl).
+pub(crate) fn get_ctx_types(fn_args: &[syn::FnArg]) -> Vec<syn::Type> {
+    fn_args
+        .iter()
+        .map(get_var_type_from_fn_arg)
+        .collect::<Option<Vec<&syn::Type>>>()
+        // TODO: instead of unwrap, should map None to an syn::parse::Error and return it
+        .unwrap()
+        .into_iter()
+        .map(|ty| {
+            let mut ty = ty.clone();
+            AssignCtxLifetime.visit_type_mut(&mut ty);
+            ty
+        })
+        .collect()
 }
 
 /// Given an optional vector of FnArgs parsed from an 'expected_context' attribute, extract the
@@ -88,32 +236,31 @@ pub(crate) fn get_ctx_type(
     expected_context: &Option<&Vec<syn::FnArg>>,
 ) -> syn::parse::Result<syn::Type> {
     expected_context.map_or(syn::parse2::<syn::Type>(quote! { () }), |fn_args| {
-        let type_vec = fn_args
-            .iter()
-            .map(get_var_type_from_fn_arg)
-            .collect::<Option<Vec<&syn::Type>>>()
-            // TODO: instead of unwrap, should map None to an syn::parse::Error and return it
-            .unwrap();
+        let type_vec = get_ctx_types(fn_args);
         syn::parse2::<syn::Type>(quote! {
             (#(#type_vec,)*)
         })
     })
 }
 
-pub(crate) fn get_ident_of_inner_type(ty: &syn::Type) -> Option<&syn::Ident> {
+/// Return the type that will actually be read from the buffer for the given type: the generic
+/// argument of a wrapper type like Vec<T> or Option<T>, or the type itself otherwise (e.g. for
+/// `u8` or `[u8; 6]`).
+pub(crate) fn get_inner_type(ty: &syn::Type) -> &syn::Type {
     if let syn::Type::Path(ref tp) = ty {
-        if tp.path.segments.len() != 1 {
-            panic!("Type path has more than one segment: {:#?}", tp);
-        }
-        let path_segment = &tp.path.segments[0];
+        let path_segment = tp
+            .path
+            .segments
+            .last()
+            .unwrap_or_else(|| panic!("Type path has no segments: {:#?}", tp));
         match path_segment.arguments {
-            syn::PathArguments::None => return Some(&path_segment.ident),
+            syn::PathArguments::None => return ty,
             syn::PathArguments::AngleBracketed(ref inner_ty) => {
                 if inner_ty.args.len() != 1 {
                     panic!("Generic type args has length != 1: {:#?}", inner_ty);
                 }
                 if let syn::GenericArgument::Type(ref ty) = inner_ty.args[0] {
-                    return get_type_ident(ty);
+                    return ty;
                 } else {
                     panic!("Generic argument wasn't a type: {:#?}", inner_ty.args[0]);
                 }
@@ -126,19 +273,113 @@ pub(crate) fn get_ident_of_inner_type(ty: &syn::Type) -> Option<&syn::Ident> {
             }
         }
     }
-    None
+    ty
+}
+
+/// The types of the elements that are read for a collection
+#[derive(Debug, PartialEq)]
+pub(crate) enum CollectionElements<'a> {
+    /// A sequence of values of the given type (e.g. a Vec<T>)
+    Sequence(&'a syn::Type),
+    /// A sequence of key/value pairs of the given types (e.g. a HashMap<K, V>)
+    Map(&'a syn::Type, &'a syn::Type),
+}
+
+/// Return the last segment of the given type's path, if it's a path
+fn get_last_path_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    match ty {
+        syn::Type::Path(ref tp) => tp.path.segments.last(),
+        _ => None,
+    }
 }
 
-/// Return true if the given type is considered to be a "collection".
+/// Return the generic type arguments of the given path segment (e.g. [K, V] for HashMap<K, V>)
+fn get_generic_type_args(path_segment: &syn::PathSegment) -> Vec<&syn::Type> {
+    match path_segment.arguments {
+        syn::PathArguments::AngleBracketed(ref args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ref ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Return the types of the elements of the given collection type.  Any type with a single generic
+/// argument is assumed to be a sequence of that type (so any FromIterator container can be read),
+/// with the exceptions of Box<[T]> and SmallVec<[T; N]>, whose elements are T, and of Option, Cow
+/// and the pointer types (Box<T>, Rc<T> and Arc<T>), which aren't collections.  HashMaps and
+/// BTreeMaps are read as key/value pairs.
+pub(crate) fn get_collection_elements(ty: &syn::Type) -> Option<CollectionElements<'_>> {
+    let path_segment = get_last_path_segment(ty)?;
+    let type_args = get_generic_type_args(path_segment);
+    if path_segment.ident == "HashMap" || path_segment.ident == "BTreeMap" {
+        return match type_args[..] {
+            [key_ty, value_ty, ..] => Some(CollectionElements::Map(key_ty, value_ty)),
+            _ => None,
+        };
+    }
+    if type_args.len() != 1 {
+        return None;
+    }
+    let element_ty = type_args[0];
+    match (path_segment.ident.to_string().as_ref(), element_ty) {
+        ("Box", syn::Type::Slice(slice)) => Some(CollectionElements::Sequence(&slice.elem)),
+        ("SmallVec", syn::Type::Array(array)) => Some(CollectionElements::Sequence(&array.elem)),
+        ("Option" | "Cow" | "Box" | "Rc" | "Arc", _) => None,
+        _ => Some(CollectionElements::Sequence(element_ty)),
+    }
+}
+
+/// Return true if the given type is considered to be a "collection", which is read via a 'count'
+/// or 'while' param.  Other container types can be read as collections too, but only if they have
+/// one of those params.
 pub(crate) fn is_collection(ty: &syn::Type) -> bool {
+    let path_segment = match get_last_path_segment(ty) {
+        Some(path_segment) => path_segment,
+        None => return false,
+    };
+    match path_segment.ident.to_string().as_ref() {
+        "Vec" | "VecDeque" | "LinkedList" | "HashMap" | "BTreeMap" => true,
+        "SmallVec" => cfg!(feature = "smallvec"),
+        "Box" => matches!(
+            get_generic_type_args(path_segment)[..],
+            [syn::Type::Slice(_)]
+        ),
+        _ => false,
+    }
+}
+
+/// If the given type is an unsigned integer type, either native (e.g. u16) or from ux (e.g. u4),
+/// return its width in bits.
+pub(crate) fn get_uint_bits(ty: &syn::Type) -> Option<u32> {
     if let syn::Type::Path(ref tp) = ty {
-        // We can't use path.get_ident here, because it doesn't work on a path whose first value
-        // has arguments.
-        if !tp.path.segments.is_empty() {
-            return tp.path.segments[0].ident == "Vec";
-        }
+        let ident = tp.path.get_ident()?.to_string();
+        let bits = ident.strip_prefix('u')?.parse::<u32>().ok()?;
+        return (1..=64).contains(&bits).then_some(bits);
+    }
+    None
+}
+
+/// If the given type is an unsigned integer type, either native (e.g. u16) or from ux (e.g. u4),
+/// return the native type that backs it, i.e. the smallest native type that can hold all of its
+/// values (e.g. u8 for u4).  Native types are backed by themselves.
+pub(crate) fn get_backing_primitive(ty: &syn::Type) -> Option<syn::Ident> {
+    let native_bits: u32 = match get_uint_bits(ty)? {
+        1..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        _ => 64,
     };
-    false
+    Some(format_ident!("u{}", native_bits))
+}
+
+/// Return true if the given type is a Vec
+pub(crate) fn is_vec(ty: &syn::Type) -> bool {
+    get_last_path_segment(ty).is_some_and(|path_segment| path_segment.ident == "Vec")
 }
 
 pub(crate) fn is_option(ty: &syn::Type) -> bool {
@@ -181,6 +422,146 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_coerce_compared_literals() {
+        let ty = syn::parse_str::<syn::Type>("u14").unwrap();
+        let coerce = |expr: &str| {
+            let expr = coerce_compared_literals(&syn::parse_str(expr).unwrap(), &ty);
+            quote! { #expr }.to_string()
+        };
+        assert_eq!(
+            coerce("|v| v > 2 && 0x1000 >= v"),
+            quote! { |v| v > <u14>::new(2) && <u14>::new(0x1000) >= v }.to_string()
+        );
+        // Literals that aren't compared directly against the argument are left alone
+        assert_eq!(
+            coerce("|v: u14| v < u14::new(0x1000) && u16::from(v) != 3"),
+            quote! { |v: u14| v < u14::new(0x1000) && u16::from(v) != 3 }.to_string()
+        );
+        assert_eq!(coerce("is_valid"), quote! { is_valid }.to_string());
+    }
+
+    #[test]
+    fn test_get_inner_type() {
+        let ty = syn::parse_str::<syn::Type>("Vec<[u8; 6]>").unwrap();
+        assert_eq!(
+            get_inner_type(&ty),
+            &syn::parse_str::<syn::Type>("[u8; 6]").unwrap()
+        );
+
+        let ty = syn::parse_str::<syn::Type>("std::net::Ipv4Addr").unwrap();
+        assert_eq!(get_inner_type(&ty), &ty);
+    }
+
+    #[test]
+    fn test_get_ctx_type_references() {
+        let fn_arg = syn::parse_str::<syn::FnArg>("hdr: &RtpHeader").unwrap();
+        let fn_arg2 = syn::parse_str::<syn::FnArg>("state: &mut Vec<Foo<'_>>").unwrap();
+        let fn_arg3 = syn::parse_str::<syn::FnArg>("other: &'a u8").unwrap();
+        let fn_arg4 = syn::parse_str::<syn::FnArg>("name: &'static str").unwrap();
+        let result = get_ctx_type(&Some(&vec![fn_arg, fn_arg2, fn_arg3, fn_arg4])).unwrap();
+
+        assert_eq!(
+            result,
+            syn::parse_str::<syn::Type>(
+                "(&'packetrs_ctx RtpHeader, &'packetrs_ctx mut Vec<Foo<'packetrs_ctx>>, &'a u8, &'static str,)"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            get_lifetimes(&result),
+            vec![
+                syn::parse_str::<syn::Lifetime>("'packetrs_ctx").unwrap(),
+                syn::parse_str::<syn::Lifetime>("'a").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_fn_args_from_lit_str() {
+        let lit_str = syn::LitStr::new(
+            "m: HashMap<u8, u16>, length: u16",
+            proc_macro2::Span::call_site(),
+        );
+        let fn_args = parse_fn_args_from_lit_str(&lit_str).unwrap();
+        assert_eq!(
+            fn_args,
+            vec![
+                syn::parse_str::<syn::FnArg>("m: HashMap<u8, u16>").unwrap(),
+                syn::parse_str::<syn::FnArg>("length: u16").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_exprs_from_lit_str() {
+        let lit_str = syn::LitStr::new("f(a, b), [1, 2], x", proc_macro2::Span::call_site());
+        let exprs = parse_exprs_from_lit_str(&lit_str).unwrap();
+        assert_eq!(
+            exprs,
+            vec![
+                syn::parse_str::<syn::Expr>("f(a, b)").unwrap(),
+                syn::parse_str::<syn::Expr>("[1, 2]").unwrap(),
+                syn::parse_str::<syn::Expr>("x").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_backing_primitive() {
+        let backing_primitive = |ty: &str| {
+            get_backing_primitive(&syn::parse_str::<syn::Type>(ty).unwrap()).map(|i| i.to_string())
+        };
+        assert_eq!(backing_primitive("u1"), Some("u8".to_owned()));
+        assert_eq!(backing_primitive("u8"), Some("u8".to_owned()));
+        assert_eq!(backing_primitive("u14"), Some("u16".to_owned()));
+        assert_eq!(backing_primitive("u24"), Some("u32".to_owned()));
+        assert_eq!(backing_primitive("u64"), Some("u64".to_owned()));
+        assert_eq!(backing_primitive("u65"), None);
+        assert_eq!(backing_primitive("i8"), None);
+        assert_eq!(backing_primitive("Vec<u8>"), None);
+    }
+
+    #[test]
+    fn test_get_collection_elements() {
+        let elements = |ty: &str| {
+            let ty = syn::parse_str::<syn::Type>(ty).unwrap();
+            get_collection_elements(&ty).map(|elements| match elements {
+                CollectionElements::Sequence(element_ty) => quote! { #element_ty }.to_string(),
+                CollectionElements::Map(key_ty, value_ty) => {
+                    quote! { #key_ty => #value_ty }.to_string()
+                }
+            })
+        };
+        assert_eq!(elements("Vec<u8>"), Some("u8".to_owned()));
+        assert_eq!(
+            elements("std::collections::VecDeque<Foo>"),
+            Some("Foo".to_owned())
+        );
+        assert_eq!(elements("Box<[u16]>"), Some("u16".to_owned()));
+        assert_eq!(elements("SmallVec<[u8; 4]>"), Some("u8".to_owned()));
+        assert_eq!(
+            elements("HashMap<u16, Attr>"),
+            Some("u16 => Attr".to_owned())
+        );
+        assert_eq!(elements("u8"), None);
+        assert_eq!(elements("Option<u8>"), None);
+        assert_eq!(elements("Box<Foo>"), None);
+        assert_eq!(elements("std::sync::Arc<Foo>"), None);
+        assert_eq!(elements("Cow<'a, str>"), None);
+    }
+
+    #[test]
+    fn test_is_collection() {
+        let is_collection = |ty: &str| is_collection(&syn::parse_str::<syn::Type>(ty).unwrap());
+        assert!(is_collection("Vec<u8>"));
+        assert!(is_collection("std::collections::VecDeque<u8>"));
+        assert!(is_collection("BTreeMap<u8, u16>"));
+        assert!(is_collection("Box<[u8]>"));
+        assert!(!is_collection("Box<Foo>"));
+        assert!(!is_collection("Option<u8>"));
+    }
+
     #[test]
     fn test_get_ctx_type_empty_types() {
         let result = get_ctx_type(&Some(&vec![]));
@@ -0,0 +1,365 @@
+use quote::quote;
+
+use crate::{
+    get_param,
+    match_pat_guard::MatchPatGuard,
+    model_types::{PacketRsAttributeParam, PacketRsEnum},
+    syn_helpers::{
+        get_collection_elements, get_inner_type, get_named_ctx_arg, get_uint_bits, is_option,
+        parse_exprs_from_lit_str, CollectionElements,
+    },
+};
+
+/// The params whose value must be one of a fixed set of names, along with those names
+const NAMED_VALUE_PARAMS: &[(&str, &[&str])] = &[
+    ("byte_order", &["big_endian", "little_endian", "network_order"]),
+    ("time_format", &["ntp64", "ntp32"]),
+    ("seek_mode", &["restore", "advance"]),
+    ("on_duplicate", &["error", "first", "last"]),
+    ("string", &["utf8", "ascii", "latin1"]),
+    ("encoding", &["quic_varint", "uleb128", "sleb128", "zigzag"]),
+    ("bit_order", &["msb", "lsb"]),
+];
+
+fn combine_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(ref mut errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// The maximum number of bits that can be peeked via 'peek_bits'
+const MAX_PEEK_BITS: u64 = 64;
+
+/// Return the string value of the param with the given name among the given params, if there is one
+fn find_str_param<'a>(params: &[&'a syn::NestedMeta], name: &str) -> Option<&'a syn::LitStr> {
+    params.iter().find_map(|param| match param {
+        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            lit: syn::Lit::Str(value),
+            ..
+        })) if path.is_ident(name) => Some(value),
+        _ => None,
+    })
+}
+
+/// Check that the 'ctx' arguments among the given params are either all named or all positional,
+/// and that they're positional if there's a custom 'reader', which takes them as a tuple.
+fn check_ctx_args(params: &[&syn::NestedMeta], errors: &mut Option<syn::Error>) {
+    let ctx = match find_str_param(params, "ctx") {
+        Some(ctx) => ctx,
+        None => return,
+    };
+    let exprs = match find_str_param(params, "ctx_delim") {
+        Some(delimiter) => ctx
+            .value()
+            .split(delimiter.value().as_str())
+            .map(syn::parse_str::<syn::Expr>)
+            .collect::<Result<Vec<syn::Expr>, syn::Error>>(),
+        None => parse_exprs_from_lit_str(ctx),
+    };
+    // Expressions that don't parse are reported when the read is generated
+    let exprs = match exprs {
+        Ok(exprs) => exprs,
+        Err(_) => return,
+    };
+    let num_named = exprs.iter().filter(|expr| get_named_ctx_arg(expr).is_some()).count();
+    let message = if num_named == 0 {
+        return;
+    } else if num_named != exprs.len() {
+        "'ctx' arguments must be either all named or all positional"
+    } else if find_str_param(params, "reader").is_some() {
+        "A custom reader takes its 'ctx' arguments as a tuple, so they must be positional"
+    } else {
+        return;
+    };
+    combine_error(errors, syn::Error::new_spanned(ctx, message));
+}
+
+fn check_param_values(params: &[&syn::NestedMeta], errors: &mut Option<syn::Error>) {
+    check_ctx_args(params, errors);
+    for param in params {
+        match param {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("peek_bits") => {
+                let num_bits = match nv.lit {
+                    syn::Lit::Str(ref value) => value.parse::<syn::LitInt>().ok(),
+                    _ => None,
+                };
+                if let Some(num_bits) = num_bits {
+                    if !matches!(num_bits.base10_parse::<u64>(), Ok(n) if n <= MAX_PEEK_BITS) {
+                        let message = format!("At most {} bits can be peeked", MAX_PEEK_BITS);
+                        combine_error(errors, syn::Error::new_spanned(&nv.lit, message));
+                    }
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) => {
+                let names = NAMED_VALUE_PARAMS
+                    .iter()
+                    .find(|(param_name, _)| nv.path.is_ident(param_name));
+                if let (Some((param_name, names)), syn::Lit::Str(value)) = (names, &nv.lit) {
+                    if !names.contains(&value.value().as_str()) {
+                        let message = format!(
+                            "Invalid '{}' value {:?}: expected one of {}",
+                            param_name,
+                            value.value(),
+                            names.join(", ")
+                        );
+                        combine_error(errors, syn::Error::new_spanned(value, message));
+                    }
+                }
+            }
+            // e.g. 'inner(...)'
+            syn::NestedMeta::Meta(syn::Meta::List(list)) => {
+                check_param_values(&list.nested.iter().collect::<Vec<_>>(), errors)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Return the packetrs params in the given attributes
+fn get_packetrs_params(attrs: &[syn::Attribute]) -> Vec<syn::NestedMeta> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("packetrs"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Check that a 'string' param (of the field itself, or in an 'inner' list for its elements) isn't
+/// on a reference: strings are copied out of the buffer, so they can't borrow from it.
+fn check_string_type(params: &[&syn::NestedMeta], ty: &syn::Type, errors: &mut Option<syn::Error>) {
+    let element_ty = if is_option(ty) {
+        Some(get_inner_type(ty))
+    } else {
+        match get_collection_elements(ty) {
+            Some(CollectionElements::Sequence(element_ty)) => Some(element_ty),
+            Some(CollectionElements::Map(_, value_ty)) => Some(value_ty),
+            None => None,
+        }
+    };
+    let inner = params.iter().find_map(|param| match param {
+        syn::NestedMeta::Meta(syn::Meta::List(list)) if list.path.is_ident("inner") => Some(list),
+        _ => None,
+    });
+    match (element_ty, inner) {
+        (Some(element_ty), Some(inner)) => {
+            check_string_type(&inner.nested.iter().collect::<Vec<_>>(), element_ty, errors)
+        }
+        // Without 'inner' params, the elements of a string field are read as strings
+        (Some(element_ty), None) => check_string_type(params, element_ty, errors),
+        (None, _) => {
+            if let (Some(_), syn::Type::Reference(_)) = (find_str_param(params, "string"), ty) {
+                let message = "Strings are copied out of the buffer, so a 'string' field can't be a reference: use a String or Cow<str> instead";
+                combine_error(errors, syn::Error::new_spanned(ty, message));
+            }
+        }
+    }
+}
+
+/// Check the params of a struct or enum variant, and those of its fields
+fn check_fields(item_params: &[syn::NestedMeta], fields: &syn::Fields, errors: &mut Option<syn::Error>) {
+    check_param_values(&item_params.iter().collect::<Vec<_>>(), errors);
+    for field in fields {
+        let field_params = get_packetrs_params(&field.attrs);
+        check_param_values(&field_params.iter().collect::<Vec<_>>(), errors);
+        // Unnamed fields get their params from the struct or enum variant
+        let params = if field.ident.is_some() {
+            field_params.iter().collect::<Vec<_>>()
+        } else {
+            item_params.iter().chain(&field_params).collect::<Vec<_>>()
+        };
+        check_string_type(&params, &field.ty, errors);
+    }
+}
+
+/// Check that every packetrs param (of the type, its variants or its fields) whose value must be
+/// one of a fixed set of names, e.g. 'byte_order', has a valid value, that a literal 'peek_bits'
+/// isn't more than can be peeked, that 'ctx' arguments aren't a mix of named and positional ones,
+/// and that 'string' fields aren't references.  This is done on the attributes themselves, before
+/// they're parsed, so that an error spanned on each invalid value can be returned.
+pub(crate) fn validate_param_values(ast: &syn::DeriveInput) -> Result<(), syn::Error> {
+    let mut errors: Option<syn::Error> = None;
+    let params = get_packetrs_params(&ast.attrs);
+    match ast.data {
+        syn::Data::Struct(ref s) => check_fields(&params, &s.fields, &mut errors),
+        syn::Data::Enum(ref e) => {
+            check_param_values(&params.iter().collect::<Vec<_>>(), &mut errors);
+            for variant in &e.variants {
+                check_fields(&get_packetrs_params(&variant.attrs), &variant.fields, &mut errors);
+            }
+        }
+        syn::Data::Union(_) => {}
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// The values matched by one case of an enum variant's id pattern, as far as they can be
+/// determined from the pattern itself.
+#[derive(Debug)]
+enum IdCase {
+    /// An integer literal (start == end) or range, inclusive
+    Range(u128, u128),
+    /// A wildcard (or a binding of one, e.g. 'x @ _'), which matches every value
+    All,
+}
+
+impl IdCase {
+    fn overlaps(&self, other: &IdCase) -> bool {
+        match (self, other) {
+            (IdCase::Range(start, end), IdCase::Range(other_start, other_end)) => {
+                start <= other_end && other_start <= end
+            }
+            _ => true,
+        }
+    }
+}
+
+fn parse_int(expr: &syn::Expr) -> Option<u128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit_int),
+            ..
+        }) => lit_int.base10_parse::<u128>().ok(),
+        syn::Expr::Group(group) => parse_int(&group.expr),
+        syn::Expr::Paren(paren) => parse_int(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Split the given pattern into its cases (the alternatives of an 'or' pattern), along with the
+/// values each matches.  Cases whose values can't be determined (e.g. tuples or consts) are
+/// skipped, since they can't be checked.  A bare identifier is skipped too: it's parsed the same
+/// way whether it's a binding or a const (e.g. 'ATTR_USERNAME'), so it can't be told apart from
+/// one, and only '_' is treated as a catch-all.
+fn get_id_cases(pat: &syn::Pat) -> Vec<(&syn::Pat, IdCase)> {
+    match pat {
+        syn::Pat::Or(pat_or) => pat_or.cases.iter().flat_map(get_id_cases).collect(),
+        syn::Pat::Lit(pat_lit) => parse_int(&pat_lit.expr)
+            .map(|value| (pat, IdCase::Range(value, value)))
+            .into_iter()
+            .collect(),
+        syn::Pat::Range(pat_range) => {
+            let range = match (parse_int(&pat_range.lo), parse_int(&pat_range.hi)) {
+                (Some(start), Some(end)) => match pat_range.limits {
+                    syn::RangeLimits::Closed(_) => Some(IdCase::Range(start, end)),
+                    syn::RangeLimits::HalfOpen(_) if end > start => {
+                        Some(IdCase::Range(start, end - 1))
+                    }
+                    syn::RangeLimits::HalfOpen(_) => None,
+                },
+                _ => None,
+            };
+            range.map(|range| (pat, range)).into_iter().collect()
+        }
+        syn::Pat::Wild(_) => vec![(pat, IdCase::All)],
+        // A binding of a subpattern (e.g. 'x @ _' or 'x @ 1..=5') matches what the subpattern does
+        syn::Pat::Ident(syn::PatIdent {
+            subpat: Some((_, subpat)),
+            ..
+        }) => get_id_cases(subpat),
+        _ => Vec::new(),
+    }
+}
+
+/// Check that no variant of the given enum has an id that can never match, because an earlier
+/// variant's id (or an earlier case of its own id) already covers the same values: i.e. duplicate
+/// literals, overlapping ranges and ids after a wildcard.  An error spanned on each offending id is
+/// returned.  Ids with guards can't be checked, and don't make any later ids unreachable.  The ids
+/// of a 'repr' enum must also fit in its repr type.
+pub(crate) fn validate_enum_ids(packetrs_enum: &PacketRsEnum) -> Result<(), syn::Error> {
+    let mut seen: Vec<(&syn::Ident, IdCase)> = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    let repr_max = get_param!(&packetrs_enum.parameters, Repr).and_then(|repr| {
+        get_uint_bits(repr).map(|bits| (quote! { #repr }.to_string(), u128::MAX >> (128 - bits)))
+    });
+
+    for variant in &packetrs_enum.variants {
+        let id: &MatchPatGuard = match get_param!(&variant.parameters, EnumId) {
+            Some(id) => id,
+            None => continue,
+        };
+        if let Some((ref repr, max)) = repr_max {
+            for (case_pat, case) in get_id_cases(&id.pat) {
+                if matches!(case, IdCase::Range(_, end) if end > max) {
+                    let message = format!(
+                        "id of variant {} is out of range: the largest {} is {}",
+                        variant.name, repr, max
+                    );
+                    combine_error(&mut errors, syn::Error::new_spanned(case_pat, message));
+                }
+            }
+        }
+        if id.guard.is_some() {
+            continue;
+        }
+        for (case_pat, case) in get_id_cases(&id.pat) {
+            if let Some((other_variant, _)) = seen.iter().find(|(_, other)| other.overlaps(&case)) {
+                let message = if *other_variant == variant.name {
+                    format!(
+                        "id of variant {} overlaps with another of its own ids",
+                        variant.name
+                    )
+                } else {
+                    format!(
+                        "id of variant {} can never match: it overlaps with the id of variant {}",
+                        variant.name, other_variant
+                    )
+                };
+                combine_error(&mut errors, syn::Error::new_spanned(case_pat, message));
+            }
+            seen.push((variant.name, case));
+        }
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cases(pat: &str) -> Vec<String> {
+        // Parsed as a match arm, since that's where top-level 'or' patterns are allowed
+        let pat = syn::parse_str::<syn::Arm>(&format!("{} => {{}}", pat))
+            .unwrap()
+            .pat;
+        get_id_cases(&pat)
+            .into_iter()
+            .map(|(_, case)| format!("{:?}", case))
+            .collect()
+    }
+
+    #[test]
+    fn test_get_id_cases() {
+        assert_eq!(cases("0x10"), vec!["Range(16, 16)"]);
+        assert_eq!(
+            cases("1 | 3..=5 | 6..8"),
+            vec!["Range(1, 1)", "Range(3, 5)", "Range(6, 7)"]
+        );
+        assert_eq!(cases("_"), vec!["All"]);
+        assert_eq!(cases("x @ _"), vec!["All"]);
+        assert_eq!(cases("x @ 1..=5"), vec!["Range(1, 5)"]);
+        assert_eq!(cases("x"), Vec::<String>::new());
+        assert_eq!(cases("ATTR_USERNAME"), Vec::<String>::new());
+        assert_eq!(cases("(1, 2)"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_id_case_overlaps() {
+        assert!(IdCase::Range(1, 5).overlaps(&IdCase::Range(5, 6)));
+        assert!(!IdCase::Range(1, 5).overlaps(&IdCase::Range(6, 6)));
+        assert!(IdCase::All.overlaps(&IdCase::Range(6, 6)));
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::bail;
+use b3::{bit_cursor::BitCursor, bit_read_exts::BitReadExts};
+
+use crate::error::PacketRsResult;
+
+/// The maximum number of bytes in a LEB128 encoding of a 64-bit value
+const MAX_LEB128_LEN: usize = 10;
+
+/// Read a QUIC variable-length integer (RFC 9000 section 16): the top 2 bits of the first byte give
+/// the length of the encoding (1, 2, 4 or 8 bytes), and the rest of the bits are the value, in
+/// network order.  Values don't have to be encoded in the minimum number of bytes, so longer
+/// encodings aren't errors.
+pub fn read_quic_varint(buf: &mut BitCursor) -> PacketRsResult<u64> {
+    let first = buf.read_u8()?;
+    let len = 1 << (first >> 6);
+    let mut value = (first & 0x3f) as u64;
+    for _ in 1..len {
+        value = (value << 8) | buf.read_u8()? as u64;
+    }
+    Ok(value)
+}
+
+/// Read an unsigned LEB128 integer: 7 bits per byte, least significant group first, with the top
+/// bit of each byte set if another byte follows.  Values which don't fit in 64 bits, and over-long
+/// encodings (with trailing zero groups), are errors.
+pub fn read_uleb128(buf: &mut BitCursor) -> PacketRsResult<u64> {
+    let mut value: u64 = 0;
+    for idx in 0..MAX_LEB128_LEN {
+        let byte = buf.read_u8()?;
+        let group = (byte & 0x7f) as u64;
+        // The last byte can only hold the single remaining bit
+        if idx == MAX_LEB128_LEN - 1 && group > 1 {
+            bail!("LEB128 value overflows 64 bits");
+        }
+        value |= group << (7 * idx);
+        if byte & 0x80 == 0 {
+            if idx > 0 && byte == 0 {
+                bail!("Over-long LEB128 encoding ({} bytes)", idx + 1);
+            }
+            return Ok(value);
+        }
+    }
+    bail!("LEB128 value is longer than {} bytes", MAX_LEB128_LEN)
+}
+
+/// Read a signed LEB128 integer: like unsigned LEB128, but the value is sign extended from the
+/// highest bit of the last group.  Values which don't fit in 64 bits, and over-long encodings (with
+/// redundant trailing sign groups), are errors.
+pub fn read_sleb128(buf: &mut BitCursor) -> PacketRsResult<i64> {
+    let mut value: i64 = 0;
+    let mut prev_byte: Option<u8> = None;
+    for idx in 0..MAX_LEB128_LEN {
+        let byte = buf.read_u8()?;
+        let group = (byte & 0x7f) as i64;
+        // The last byte holds the single remaining bit, which must be sign extended into the rest
+        if idx == MAX_LEB128_LEN - 1 && group != 0 && group != 0x7f {
+            bail!("LEB128 value overflows 64 bits");
+        }
+        value |= group << (7 * idx);
+        if byte & 0x80 == 0 {
+            let is_negative = byte & 0x40 != 0;
+            // A final group which is all sign bits is redundant if the previous group's top bit
+            // already has the same sign
+            if let Some(prev_byte) = prev_byte {
+                let prev_is_negative = prev_byte & 0x40 != 0;
+                if (byte == 0x00 && !prev_is_negative) || (byte == 0x7f && prev_is_negative) {
+                    bail!("Over-long LEB128 encoding ({} bytes)", idx + 1);
+                }
+            }
+            let shift = 7 * (idx + 1);
+            if is_negative && shift < 64 {
+                value |= -1i64 << shift;
+            }
+            return Ok(value);
+        }
+        prev_byte = Some(byte);
+    }
+    bail!("LEB128 value is longer than {} bytes", MAX_LEB128_LEN)
+}
+
+/// Read a zigzag-encoded signed integer (as used by protobuf's sint types): an unsigned LEB128
+/// value in which the lowest bit is the sign, so small negative values have short encodings.
+pub fn read_zigzag(buf: &mut BitCursor) -> PacketRsResult<i64> {
+    let value = read_uleb128(buf)?;
+    Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read<T>(f: fn(&mut BitCursor) -> PacketRsResult<T>, data: &[u8]) -> PacketRsResult<T> {
+        let mut buf = BitCursor::from_vec(data.to_vec());
+        let value = f(&mut buf)?;
+        assert_eq!(buf.bits_remaining(), 0);
+        Ok(value)
+    }
+
+    #[test]
+    fn test_read_quic_varint() {
+        // The examples from RFC 9000 appendix A.1
+        let eight_bytes = [0xc2, 0x19, 0x7c, 0x5e, 0xff, 0x14, 0xe8, 0x8c];
+        assert_eq!(
+            read(read_quic_varint, &eight_bytes).unwrap(),
+            151_288_809_941_952_652
+        );
+        let four_bytes = [0x9d, 0x7f, 0x3e, 0x7d];
+        assert_eq!(read(read_quic_varint, &four_bytes).unwrap(), 494_878_333);
+        assert_eq!(read(read_quic_varint, &[0x7b, 0xbd]).unwrap(), 15_293);
+        assert_eq!(read(read_quic_varint, &[0x25]).unwrap(), 37);
+        assert_eq!(read(read_quic_varint, &[0x40, 0x25]).unwrap(), 37);
+    }
+
+    #[test]
+    fn test_read_uleb128() {
+        assert_eq!(read(read_uleb128, &[0x02]).unwrap(), 2);
+        assert_eq!(read(read_uleb128, &[0xe5, 0x8e, 0x26]).unwrap(), 624_485);
+        let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(read(read_uleb128, &max).unwrap(), u64::MAX);
+
+        let overflow = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        assert!(read(read_uleb128, &overflow).is_err());
+        assert!(read(read_uleb128, &[0x82, 0x00]).is_err());
+        assert!(read(read_uleb128, &[0x82]).is_err());
+    }
+
+    #[test]
+    fn test_read_sleb128() {
+        assert_eq!(read(read_sleb128, &[0x02]).unwrap(), 2);
+        assert_eq!(read(read_sleb128, &[0x7e]).unwrap(), -2);
+        assert_eq!(read(read_sleb128, &[0xc0, 0xbb, 0x78]).unwrap(), -123_456);
+        // 64 needs a second byte, since 0x40 alone is the sign bit
+        assert_eq!(read(read_sleb128, &[0xc0, 0x00]).unwrap(), 64);
+        let min = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f];
+        assert_eq!(read(read_sleb128, &min).unwrap(), i64::MIN);
+
+        assert!(read(read_sleb128, &[0x82, 0x00]).is_err());
+        assert!(read(read_sleb128, &[0xfe, 0x7f]).is_err());
+    }
+
+    #[test]
+    fn test_read_zigzag() {
+        assert_eq!(read(read_zigzag, &[0x00]).unwrap(), 0);
+        assert_eq!(read(read_zigzag, &[0x01]).unwrap(), -1);
+        assert_eq!(read(read_zigzag, &[0x02]).unwrap(), 1);
+        assert_eq!(read(read_zigzag, &[0x03]).unwrap(), -2);
+        let min = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(read(read_zigzag, &min).unwrap(), i64::MIN);
+    }
+}
@@ -15,3 +15,16 @@ pub fn derive_packetrs_read(item: TokenStream) -> TokenStream {
         Err(err) => TokenStream::from(err.to_compile_error()),
     }
 }
+
+#[proc_macro_attribute]
+/// An alternative to `#[derive(PacketrsRead)]` which also allows the definition of the type to be
+/// modified, which is required for 'temp' fields.
+pub fn packetrs_derive(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = parse_macro_input!(attr as proc_macro2::TokenStream);
+    let item = parse_macro_input!(item as proc_macro2::TokenStream);
+
+    match packetrs_impl::packetrs_derive(attr, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
@@ -12,7 +12,17 @@ pub mod anyhow {
     pub use packetrs_impl::anyhow::*;
 }
 
+pub use packetrs_impl::bitmap;
+pub use packetrs_impl::dns;
 pub use packetrs_impl::error;
+pub use packetrs_impl::ntp;
 pub use packetrs_impl::packetrs_read;
+pub use packetrs_impl::read_options;
+pub use packetrs_impl::string;
+pub use packetrs_impl::varint;
+#[cfg(feature = "smallvec")]
+pub use packetrs_impl::smallvec;
 #[doc(inline)]
 pub use packetrs_macro::PacketrsRead;
+#[doc(inline)]
+pub use packetrs_macro::packetrs_derive;
@@ -1,7 +1,8 @@
 pub use crate::{
     anyhow::*, 
     error::PacketRsResult,
-    packetrs_read::PacketrsRead, ux::*, PacketrsRead,
+    read_options::ReadOptions,
+    packetrs_read::{PacketrsRead, PacketrsReadExt}, ux::*, PacketrsRead, packetrs_derive,
 };
 
 pub use packetrs_impl::b3::{bitvec, bit_cursor::BitCursor, bit_read::BitRead, bit_read_exts::BitReadExts, bit_vec::BitVec, byte_order::*};
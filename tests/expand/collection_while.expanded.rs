@@ -8,14 +8,42 @@ impl ::packetrs::packetrs_read::PacketrsRead<()> for MyStruct {
         buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
         ctx: (),
     ) -> ::packetrs::error::PacketRsResult<Self> {
-        let values = (|| {
-            let mut values = Vec::<::packetrs::error::PacketRsResult<u32>>::new();
-            while 1 > 2 {
-                values.push(u32::read::<NetworkOrder>(buf, ()).map_err(|e| e.into()));
-            }
-            values.into_iter().collect::<::packetrs::error::PacketRsResult<Vec<u32>>>()
-        })()
-            .context("values")?;
-        Ok(Self { values })
+        <Self as ::packetrs::packetrs_read::PacketrsRead<()>>::read_with_options::<T>(
+            buf,
+            ctx,
+            &mut ::packetrs::read_options::ReadOptions::default(),
+        )
+    }
+    fn read_with_options<T: ::packetrs::b3::byte_order::ByteOrder>(
+        buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
+        ctx: (),
+        read_options: &mut ::packetrs::read_options::ReadOptions,
+    ) -> ::packetrs::error::PacketRsResult<Self> {
+        read_options.enter()?;
+        let result = (|| -> ::packetrs::error::PacketRsResult<Self> {
+            let values = (|| -> ::packetrs::error::PacketRsResult<Vec<u32>> {
+                let mut __packetrs_values = Vec::<u32>::new();
+                loop {
+                    #[allow(unused_variables)]
+                    let index = __packetrs_values.len();
+                    #[allow(unused_variables)]
+                    let elements: &[u32] = &__packetrs_values;
+                    if !(1 > 2) {
+                        break;
+                    }
+                    read_options.check_while_iterations(index)?;
+                    read_options.check_collection_len(index + 1)?;
+                    read_options.allocate(::core::mem::size_of::<u32>())?;
+                    let __packetrs_value =
+                        u32::read_with_options::<NetworkOrder>(buf, (), read_options)?;
+                    __packetrs_values.push(__packetrs_value);
+                }
+                Ok(__packetrs_values)
+            })()
+                .context("values")?;
+            Ok(Self { values })
+        })();
+        read_options.exit();
+        result
     }
 }
@@ -13,27 +13,57 @@ impl ::packetrs::packetrs_read::PacketrsRead<(u32, u32)> for MyEnum {
         buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
         ctx: (u32, u32),
     ) -> ::packetrs::error::PacketRsResult<Self> {
-        let left: u32 = ctx.0;
-        let right: u32 = ctx.1;
-        match left + right {
-            1 => (|| Ok(MyEnum::One))().context("One"),
-            2 => (|| Ok(MyEnum::Two))().context("Two"),
-            3 => (|| Ok(MyEnum::Three))().context("Three"),
-            v @ _ => {
-                ::core::panicking::panic_fmt(
-                    ::core::fmt::Arguments::new_v1(
-                        &["not yet implemented: "],
-                        &[
-                            ::core::fmt::ArgumentV1::new_display(
-                                &::core::fmt::Arguments::new_v1(
-                                    &["Value of ", " is not implemented"],
-                                    &[::core::fmt::ArgumentV1::new_debug(&v)],
+        <Self as ::packetrs::packetrs_read::PacketrsRead<(u32, u32)>>::read_with_options::<T>(
+            buf,
+            ctx,
+            &mut ::packetrs::read_options::ReadOptions::default(),
+        )
+    }
+    fn read_with_options<T: ::packetrs::b3::byte_order::ByteOrder>(
+        buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
+        ctx: (u32, u32),
+        read_options: &mut ::packetrs::read_options::ReadOptions,
+    ) -> ::packetrs::error::PacketRsResult<Self> {
+        read_options.enter()?;
+        let result = (|| -> ::packetrs::error::PacketRsResult<Self> {
+            let left: u32 = ctx.0;
+            let right: u32 = ctx.1;
+            match left + right {
+                1 => (|| Ok(MyEnum::One))().context("One"),
+                2 => (|| Ok(MyEnum::Two))().context("Two"),
+                3 => (|| Ok(MyEnum::Three))().context("Three"),
+                v => {
+                    Err(
+                        ::anyhow::Error::msg({
+                            let res = ::alloc::fmt::format(
+                                ::core::fmt::Arguments::new_v1(
+                                    &["", " is not a known ", " value"],
+                                    &[
+                                        ::core::fmt::ArgumentV1::new_debug(&v),
+                                        ::core::fmt::ArgumentV1::new_display(&"MyEnum"),
+                                    ],
                                 ),
-                            ),
-                        ],
-                    ),
-                );
+                            );
+                            res
+                        }),
+                    )
+                }
             }
-        }
+        })();
+        read_options.exit();
+        result
     }
 }
+const _: () = {
+    struct MyEnumPacketrsCtx {
+        left: u32,
+        right: u32,
+    }
+    impl ::packetrs::packetrs_read::PacketrsNamedCtx<'_> for MyEnum {
+        type Ctx = (u32, u32);
+        type Named = MyEnumPacketrsCtx;
+        fn into_ctx(named: Self::Named) -> Self::Ctx {
+            (named.left, named.right)
+        }
+    }
+};
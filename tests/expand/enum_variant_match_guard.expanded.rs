@@ -9,24 +9,46 @@ impl ::packetrs::packetrs_read::PacketrsRead<(u32,)> for MyEnum {
         buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
         ctx: (u32,),
     ) -> ::packetrs::error::PacketRsResult<Self> {
-        let value: u32 = ctx.0;
-        match value {
-            x if x > 10 => (|| Ok(MyEnum::One))().context("One"),
-            v @ _ => {
-                ::core::panicking::panic_fmt(
-                    ::core::fmt::Arguments::new_v1(
-                        &["not yet implemented: "],
-                        &[
-                            ::core::fmt::ArgumentV1::new_display(
-                                &::core::fmt::Arguments::new_v1(
-                                    &["Value of ", " is not implemented"],
-                                    &[::core::fmt::ArgumentV1::new_debug(&v)],
-                                ),
-                            ),
-                        ],
-                    ),
-                );
+        <Self as ::packetrs::packetrs_read::PacketrsRead<(u32,)>>::read_with_options::<T>(
+            buf,
+            ctx,
+            &mut ::packetrs::read_options::ReadOptions::default(),
+        )
+    }
+    fn read_with_options<T: ::packetrs::b3::byte_order::ByteOrder>(
+        buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
+        ctx: (u32,),
+        read_options: &mut ::packetrs::read_options::ReadOptions,
+    ) -> ::packetrs::error::PacketRsResult<Self> {
+        read_options.enter()?;
+        let result = (|| -> ::packetrs::error::PacketRsResult<Self> {
+            let value: u32 = ctx.0;
+            match value {
+                x if x > 10 => (|| Ok(MyEnum::One))().context("One"),
+                v => {
+                    Err(
+                        ::packetrs::error::UnknownEnumValue {
+                            enum_name: "MyEnum",
+                            value: <u64 as ::core::convert::From<u32>>::from(v),
+                        }
+                            .into(),
+                    )
+                }
             }
-        }
+        })();
+        read_options.exit();
+        result
     }
 }
+const _: () = {
+    struct MyEnumPacketrsCtx {
+        value: u32,
+    }
+    impl ::packetrs::packetrs_read::PacketrsNamedCtx<'_> for MyEnum {
+        type Ctx = (u32,);
+        type Named = MyEnumPacketrsCtx;
+        fn into_ctx(named: Self::Named) -> Self::Ctx {
+            (named.value,)
+        }
+    }
+};
@@ -14,6 +14,22 @@ impl ::packetrs::packetrs_read::PacketrsRead<()> for MyStruct {
         buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
         ctx: (),
     ) -> ::packetrs::error::PacketRsResult<Self> {
-        custom_reader(buf, ()).context("custom_reader")
+        <Self as ::packetrs::packetrs_read::PacketrsRead<()>>::read_with_options::<T>(
+            buf,
+            ctx,
+            &mut ::packetrs::read_options::ReadOptions::default(),
+        )
+    }
+    fn read_with_options<T: ::packetrs::b3::byte_order::ByteOrder>(
+        buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
+        ctx: (),
+        read_options: &mut ::packetrs::read_options::ReadOptions,
+    ) -> ::packetrs::error::PacketRsResult<Self> {
+        read_options.enter()?;
+        let result = (|| -> ::packetrs::error::PacketRsResult<Self> {
+            custom_reader(buf, ()).context("custom_reader")
+        })();
+        read_options.exit();
+        result
     }
 }
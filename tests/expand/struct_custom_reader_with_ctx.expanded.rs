@@ -14,8 +14,37 @@ impl ::packetrs::packetrs_read::PacketrsRead<(u8, u16)> for MyStruct {
         buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
         ctx: (u8, u16),
     ) -> ::packetrs::error::PacketRsResult<Self> {
-        let size: u8 = ctx.0;
-        let ty: u16 = ctx.1;
-        custom_reader(buf, (size, ty)).context("custom_reader")
+        <Self as ::packetrs::packetrs_read::PacketrsRead<(u8, u16)>>::read_with_options::<T>(
+            buf,
+            ctx,
+            &mut ::packetrs::read_options::ReadOptions::default(),
+        )
+    }
+    fn read_with_options<T: ::packetrs::b3::byte_order::ByteOrder>(
+        buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
+        ctx: (u8, u16),
+        read_options: &mut ::packetrs::read_options::ReadOptions,
+    ) -> ::packetrs::error::PacketRsResult<Self> {
+        read_options.enter()?;
+        let result = (|| -> ::packetrs::error::PacketRsResult<Self> {
+            let size: u8 = ctx.0;
+            let ty: u16 = ctx.1;
+            custom_reader(buf, (size, ty)).context("custom_reader")
+        })();
+        read_options.exit();
+        result
     }
 }
+const _: () = {
+    struct MyStructPacketrsCtx {
+        size: u8,
+        ty: u16,
+    }
+    impl ::packetrs::packetrs_read::PacketrsNamedCtx<'_> for MyStruct {
+        type Ctx = (u8, u16);
+        type Named = MyStructPacketrsCtx;
+        fn into_ctx(named: Self::Named) -> Self::Ctx {
+            (named.size, named.ty)
+        }
+    }
+};
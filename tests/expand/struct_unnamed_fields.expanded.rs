@@ -5,8 +5,26 @@ impl ::packetrs::packetrs_read::PacketrsRead<()> for MyStruct {
         buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
         ctx: (),
     ) -> ::packetrs::error::PacketRsResult<Self> {
-        let field_0 = u8::read::<NetworkOrder>(buf, ()).context("field_0")?;
-        let field_1 = u16::read::<NetworkOrder>(buf, ()).context("field_1")?;
-        Ok(Self(field_0, field_1))
+        <Self as ::packetrs::packetrs_read::PacketrsRead<()>>::read_with_options::<T>(
+            buf,
+            ctx,
+            &mut ::packetrs::read_options::ReadOptions::default(),
+        )
+    }
+    fn read_with_options<T: ::packetrs::b3::byte_order::ByteOrder>(
+        buf: &mut ::packetrs::b3::bit_cursor::BitCursor,
+        ctx: (),
+        read_options: &mut ::packetrs::read_options::ReadOptions,
+    ) -> ::packetrs::error::PacketRsResult<Self> {
+        read_options.enter()?;
+        let result = (|| -> ::packetrs::error::PacketRsResult<Self> {
+            let field_0 = u8::read_with_options::<NetworkOrder>(buf, (), read_options)
+                .context("field_0")?;
+            let field_1 = u16::read_with_options::<NetworkOrder>(buf, (), read_options)
+                .context("field_1")?;
+            Ok(Self(field_0, field_1))
+        })();
+        read_options.exit();
+        result
     }
 }
@@ -0,0 +1,27 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_type: u16, length: u16")]
+struct Attribute {
+    #[packetrs(read_value = "message_type")]
+    message_type: u16,
+    #[packetrs(read_value = "length")]
+    length: u16,
+}
+
+fn read_length(buf: &mut BitCursor, ctx: (u16,)) -> PacketRsResult<u16> {
+    Ok(u16::read::<NetworkOrder>(buf, ())? + ctx.0)
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Message {
+    attr_type: u16,
+    attr_len: u16,
+    #[packetrs(ctx = "attr_type, length = attr_len")]
+    attr: Attribute,
+    #[packetrs(reader = "read_length")]
+    #[packetrs(ctx = "offset = attr_len")]
+    len: u16,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use packetrs::prelude::*;
+
+#[derive(PacketrsRead)]
+#[packetrs(byte_order = "blah")]
+struct Foo {
+    #[packetrs(count = "2", inner(time_format = "ntp16"))]
+    timestamps: Vec<u32>,
+    #[packetrs(string = "utf16", cstr)]
+    name: String,
+    #[packetrs(encoding = "leb128")]
+    length: u32,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use packetrs::prelude::*;
+
+#[derive(PacketrsRead)]
+#[packetrs(required_ctx = "attr_type: u16", key = "attr_type")]
+enum Attribute {
+    #[packetrs(id = "0x0001")]
+    Username,
+    #[packetrs(id = "0x0001")]
+    Password,
+    #[packetrs(id = "0x0010..=0x0020")]
+    Reserved,
+    #[packetrs(id = "0x0018")]
+    Realm,
+}
+
+fn main() {}
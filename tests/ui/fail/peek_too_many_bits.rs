@@ -1,8 +1,10 @@
 use packetrs::prelude::*;
 
 #[derive(PacketrsRead)]
-#[packetrs(byte_order = "blah")]
 struct Foo {
+    #[packetrs(peek_bits = "72")]
+    first_bits: u64,
+    value: [u8; 8],
 }
 
 fn main() {}
@@ -0,0 +1,19 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, Clone, Copy, PacketrsRead)]
+#[packetrs(repr = "u4")]
+enum Version {
+    #[packetrs(id = "4")]
+    V4,
+    #[packetrs(id = "20")]
+    V20,
+}
+
+#[derive(Debug, Clone, Copy, PacketrsRead)]
+#[packetrs(repr = "u2")]
+enum MessageClass {
+    Request = 0b00,
+    Response = 0b100,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Username<'a> {
+    length: u16,
+    #[packetrs(string = "utf8", byte_len = "length")]
+    username: &'a str,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Tokens<'a> {
+    #[packetrs(count = "2", inner(string = "ascii", cstr))]
+    tokens: Vec<&'a str>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use packetrs::prelude::*;
+
+#[derive(PacketrsRead)]
+struct Foo {
+    #[packetrs(temp)]
+    length: u8,
+    #[packetrs(count = "length")]
+    values: Vec<u8>,
+}
+
+fn main() {}
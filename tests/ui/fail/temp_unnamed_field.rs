@@ -0,0 +1,10 @@
+use packetrs::prelude::*;
+
+#[packetrs_derive(PacketrsRead)]
+#[packetrs(required_ctx = "ty: u8", key = "ty")]
+enum Foo {
+    #[packetrs(id = "1")]
+    Values(#[packetrs(temp)] u8, u16),
+}
+
+fn main() {}
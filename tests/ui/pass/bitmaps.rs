@@ -0,0 +1,45 @@
+use packetrs::prelude::*;
+
+// An RTCP generic NACK: a packet ID and a bitmask of the lost packets that follow it
+#[derive(Debug, PacketrsRead)]
+struct GenericNack {
+    packet_id: u16,
+    #[packetrs(bits = "16")]
+    lost_packets: BitVec,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Flags {
+    num_flags: u8,
+    #[packetrs(bits = "num_flags", bit_order = "lsb")]
+    flags: Vec<bool>,
+}
+
+// 12 flags, least significant bit first: the last 4 are the low bits of the second byte, and its
+// high bits are padding
+#[derive(Debug, PacketrsRead)]
+struct Capabilities {
+    #[packetrs(bits = "12", bit_order = "lsb")]
+    capabilities: Vec<bool>,
+    version: u8,
+}
+
+fn main() {
+    let nack = GenericNack::from_bytes(&[0x00, 0x64, 0b1000_0000, 0b0000_0001]).unwrap();
+    assert_eq!(nack.packet_id, 100);
+    assert_eq!(nack.lost_packets.len(), 16);
+    assert!(nack.lost_packets[0]);
+    assert!(!nack.lost_packets[1]);
+    assert!(nack.lost_packets[15]);
+
+    let flags = Flags::from_bytes(&[8, 0b0000_0101]).unwrap();
+    assert_eq!(
+        flags.flags,
+        vec![true, false, true, false, false, false, false, false]
+    );
+
+    let capabilities = Capabilities::from_bytes(&[0b0000_0001, 0b1111_1000, 2]).unwrap();
+    let set: Vec<usize> = (0..12).filter(|&i| capabilities.capabilities[i]).collect();
+    assert_eq!(set, vec![0, 11]);
+    assert_eq!(capabilities.version, 2);
+}
@@ -0,0 +1,33 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use packetrs::prelude::*;
+
+#[derive(PacketrsRead)]
+struct MyStruct {
+    v4: Ipv4Addr,
+    v6: Ipv6Addr,
+    mac: [u8; 6],
+    #[packetrs(time_format = "ntp64")]
+    sent: SystemTime,
+    #[packetrs(time_format = "ntp32")]
+    delay: Duration,
+}
+
+fn main() {
+    let mut data: Vec<u8> = vec![192, 168, 1, 1];
+    data.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    data.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]);
+    // 2208988810 seconds after the NTP epoch (10 seconds after the UNIX epoch) plus half a second
+    data.extend_from_slice(&[0x83, 0xaa, 0x7e, 0x8a, 0x80, 0x00, 0x00, 0x00]);
+    // 1.5 seconds
+    data.extend_from_slice(&[0x00, 0x01, 0x80, 0x00]);
+    let mut buf = BitCursor::from_vec(data);
+
+    let ms = MyStruct::read::<NetworkOrder>(&mut buf, ()).unwrap();
+    assert_eq!(ms.v4, Ipv4Addr::new(192, 168, 1, 1));
+    assert_eq!(ms.v6, "2001:db8::1".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(ms.mac, [0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]);
+    assert_eq!(ms.sent, UNIX_EPOCH + Duration::from_millis(10_500));
+    assert_eq!(ms.delay, Duration::from_millis(1_500));
+}
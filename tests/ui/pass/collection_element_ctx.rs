@@ -0,0 +1,56 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "index: usize, prev_total: u32")]
+struct Chunk {
+    #[packetrs(read_value = "index")]
+    index: usize,
+    delta: u8,
+    #[packetrs(read_value = "prev_total + delta as u32")]
+    total: u32,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Chunks {
+    count: u8,
+    #[packetrs(count = "count", ctx = "index, elements.last().map_or(0, |c| c.total)")]
+    chunks: Vec<Chunk>,
+    // Values are read until their sum reaches 10
+    #[packetrs(while = "elements.iter().map(|v| *v as u32).sum::<u32>() < 10")]
+    values: Vec<u8>,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "count: u8, value: u8")]
+struct Entry {
+    #[packetrs(read_value = "count")]
+    count: u8,
+    #[packetrs(read_value = "value")]
+    value: u8,
+}
+
+// Fields with the same names as the collection read's own locals aren't shadowed by them
+#[derive(Debug, PacketrsRead)]
+struct Entries {
+    count: u8,
+    value: u8,
+    values: u8,
+    #[packetrs(count = "count", ctx = "count, value")]
+    entries: Vec<Entry>,
+    #[packetrs(while = "elements.len() < values as usize")]
+    rest: Vec<u8>,
+}
+
+fn main() {
+    let chunks = Chunks::from_bytes(&[3, 1, 2, 3, 4, 5, 1]).unwrap();
+    let indices: Vec<usize> = chunks.chunks.iter().map(|c| c.index).collect();
+    assert_eq!(indices, vec![0, 1, 2]);
+    let totals: Vec<u32> = chunks.chunks.iter().map(|c| c.total).collect();
+    assert_eq!(totals, vec![1, 3, 6]);
+    assert_eq!(chunks.values, vec![4, 5, 1]);
+
+    let entries = Entries::from_bytes(&[2, 7, 1, 0xaa]).unwrap();
+    assert_eq!(entries.entries.len(), 2);
+    assert!(entries.entries.iter().all(|e| e.count == 2 && e.value == 7));
+    assert_eq!(entries.rest, vec![0xaa]);
+}
@@ -0,0 +1,93 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+use packetrs::prelude::*;
+
+#[derive(Debug, PartialEq, PacketrsRead)]
+#[packetrs(required_ctx = "tlv_type: u8")]
+struct TlvValue {
+    #[packetrs(read_value = "tlv_type")]
+    tlv_type: u8,
+    length: u8,
+    #[packetrs(count = "length")]
+    data: Box<[u8]>,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Message {
+    num_values: u8,
+    #[packetrs(count = "num_values")]
+    values: VecDeque<u16>,
+    num_tlvs: u8,
+    // Each TLV is read as its type (the key) and then the rest of the TLV (the value)
+    #[packetrs(count = "num_tlvs", ctx = "key")]
+    tlvs: HashMap<u8, TlvValue>,
+    num_options: u8,
+    #[packetrs(count = "num_options", on_duplicate = "last")]
+    options: BTreeMap<u8, u8>,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Tlvs {
+    num_tlvs: u8,
+    #[packetrs(count = "num_tlvs", ctx = "key")]
+    tlvs: HashMap<u8, TlvValue>,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Delays {
+    num_delays: u8,
+    // The time format is only for the values: the keys are read as plain u8s
+    #[packetrs(count = "num_delays", time_format = "ntp32")]
+    delays: BTreeMap<u8, Duration>,
+}
+
+#[derive(Debug, PartialEq, PacketrsRead)]
+#[packetrs(tag = "u8")]
+enum Payload {
+    // The variant's count applies to its unnamed fields, but only the Vec is a collection
+    #[packetrs(id = "1", count = "2")]
+    Data(u8, Vec<u8>),
+}
+
+// Fields with the same names as the map read's own locals aren't shadowed by them
+#[derive(Debug, PacketrsRead)]
+struct Settings {
+    map: u8,
+    value: u8,
+    #[packetrs(count = "map", ctx = "key + value")]
+    settings: HashMap<u8, TlvValue>,
+    key: u8,
+    #[packetrs(when = "key == map + value")]
+    checksum: Option<u8>,
+}
+
+fn main() {
+    let values: &[u8] = &[2, 0x00, 0x01, 0x00, 0x02];
+    let tlvs: &[u8] = &[2, 1, 2, 0xaa, 0xbb, 5, 0];
+    let options: &[u8] = &[3, 1, 10, 2, 20, 1, 30];
+    let data = [values, tlvs, options].concat();
+    let message = Message::from_bytes(&data).unwrap();
+    assert_eq!(message.values, VecDeque::from(vec![1, 2]));
+    assert_eq!(&*message.tlvs[&1].data, &[0xaa, 0xbb]);
+    assert_eq!(message.tlvs[&5].tlv_type, 5);
+    assert!(message.tlvs[&5].data.is_empty());
+    // The last value of a duplicate key is kept
+    assert_eq!(
+        message.options.into_iter().collect::<Vec<_>>(),
+        vec![(1, 30), (2, 20)]
+    );
+
+    // Duplicate keys are an error by default
+    assert!(Tlvs::from_bytes(&[2, 1, 0, 1, 0]).is_err());
+
+    let delays = Delays::from_bytes(&[1, 7, 0x00, 0x01, 0x80, 0x00]).unwrap();
+    assert_eq!(delays.delays[&7], Duration::from_millis(1500));
+
+    let settings = Settings::from_bytes(&[1, 2, 3, 0, 3, 0xcc]).unwrap();
+    assert_eq!(settings.settings[&3].tlv_type, 5);
+    assert_eq!(settings.checksum, Some(0xcc));
+
+    let payload = Payload::from_bytes(&[1, 0x2a, 0xaa, 0xbb]).unwrap();
+    assert_eq!(payload, Payload::Data(0x2a, vec![0xaa, 0xbb]));
+}
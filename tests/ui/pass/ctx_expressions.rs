@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "lengths: &HashMap<u8, u16>, kind: u8")]
+struct Value {
+    #[packetrs(count = "lengths.get(&kind).copied().unwrap_or(0)")]
+    data: Vec<u8>,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "lengths: &HashMap<u8, u16>")]
+struct Message {
+    kind: u8,
+    other_kind: u8,
+    #[packetrs(ctx = "lengths, std::cmp::max(kind, other_kind)")]
+    value: Value,
+    // The legacy 'ctx_delim' is still accepted
+    #[packetrs(ctx = "lengths; kind", ctx_delim = ";")]
+    other_value: Value,
+}
+
+fn main() {
+    let lengths: HashMap<u8, u16> = [(1, 1), (2, 2)].into_iter().collect();
+    let data: Vec<u8> = vec![0x01, 0x02, 0xaa, 0xbb, 0xcc];
+    let mut buf = BitCursor::from_vec(data);
+
+    let message = Message::read::<NetworkOrder>(&mut buf, (&lengths,)).unwrap();
+    assert_eq!(message.value.data, vec![0xaa, 0xbb]);
+    assert_eq!(message.other_value.data, vec![0xcc]);
+}
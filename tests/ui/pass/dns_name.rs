@@ -0,0 +1,67 @@
+use packetrs::dns::DnsName;
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Question {
+    name: DnsName,
+    qtype: u16,
+    qclass: u16,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Message {
+    num_questions: u8,
+    #[packetrs(count = "num_questions")]
+    questions: Vec<Question>,
+}
+
+// A message which doesn't start at position 0 of the buffer: its start is passed to its names,
+// and can be used to read at offsets within it
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_start: u64")]
+struct FramedQuestion {
+    #[packetrs(ctx = "message_start")]
+    name: DnsName,
+    qtype: u16,
+    qclass: u16,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_start: u64")]
+struct FramedMessage {
+    num_questions: u8,
+    #[packetrs(count = "num_questions", ctx = "message_start")]
+    questions: Vec<FramedQuestion>,
+    #[packetrs(at = "message_start + 1", seek_from_start, ctx = "message_start")]
+    first_name: DnsName,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Frame {
+    header_len: u8,
+    #[packetrs(count = "header_len - 1")]
+    header: Vec<u8>,
+    #[packetrs(ctx = "header_len as u64")]
+    message: FramedMessage,
+}
+
+fn main() {
+    let mut data: Vec<u8> = vec![0x02];
+    // "example.com" at offset 1
+    data.extend_from_slice(b"\x07example\x03com\x00\x00\x01\x00\x01");
+    // "www" followed by a pointer to "example.com"
+    data.extend_from_slice(b"\x03www\xc0\x01\x00\x1c\x00\x01");
+    let message = Message::from_bytes(&data).unwrap();
+    assert_eq!(message.questions[0].name.to_string(), "example.com");
+    assert_eq!(message.questions[1].name.to_string(), "www.example.com");
+    assert_eq!(message.questions[1].qtype, 0x1c);
+
+    // The same message after a 3 byte header
+    let framed_data = [&[3, 0xaa, 0xbb][..], &data].concat();
+    let frame = Frame::from_bytes(&framed_data).unwrap();
+    assert_eq!(frame.header, vec![0xaa, 0xbb]);
+    let questions = &frame.message.questions;
+    assert_eq!(questions[0].name.to_string(), "example.com");
+    assert_eq!(questions[1].name.to_string(), "www.example.com");
+    assert_eq!(frame.message.first_name.to_string(), "example.com");
+}
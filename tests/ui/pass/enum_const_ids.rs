@@ -0,0 +1,38 @@
+use packetrs::prelude::*;
+
+const ATTR_USERNAME: u16 = 0x0006;
+const ATTR_MESSAGE_INTEGRITY: u16 = 0x0008;
+const ATTR_FINGERPRINT: u16 = 0x8028;
+
+// Const ids can't be checked for overlaps at compile time, so they don't prevent later ids from
+// being accepted
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_type: u16, length: u16", key = "message_type")]
+enum StunAttribute {
+    #[packetrs(id = "ATTR_USERNAME", count = "length")]
+    Username(Vec<u8>),
+    #[packetrs(id = "ATTR_MESSAGE_INTEGRITY", count = "length")]
+    MessageIntegrity(Vec<u8>),
+    #[packetrs(id = "ATTR_FINGERPRINT")]
+    Fingerprint { crc: u32 },
+    #[packetrs(id = "0x8000..=0xffff", count = "length")]
+    Unknown(Vec<u8>),
+}
+
+fn main() {
+    let attr = StunAttribute::from_bytes_with_ctx(&[0x61, 0x62], (ATTR_USERNAME, 2)).unwrap();
+    match attr {
+        StunAttribute::Username(ref value) => assert_eq!(value, b"ab"),
+        _ => panic!("Wrong attribute: {:?}", attr),
+    }
+
+    let attr = StunAttribute::from_bytes_with_ctx(&[0x01], (ATTR_MESSAGE_INTEGRITY, 1)).unwrap();
+    assert!(matches!(attr, StunAttribute::MessageIntegrity(_)));
+
+    let attr = StunAttribute::from_bytes_with_ctx(&[0x00, 0x00, 0x00, 0x01], (ATTR_FINGERPRINT, 4))
+        .unwrap();
+    assert!(matches!(attr, StunAttribute::Fingerprint { crc: 1 }));
+
+    let attr = StunAttribute::from_bytes_with_ctx(&[0x01], (0x8001, 1)).unwrap();
+    assert!(matches!(attr, StunAttribute::Unknown(_)));
+}
@@ -0,0 +1,43 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_type: u16, length: u16", key = "message_type")]
+enum StunAttribute {
+    #[packetrs(id = "0x0006", count = "length")]
+    Username(Vec<u8>),
+    #[packetrs(id = "0x0008", count = "length")]
+    MessageIntegrity(Vec<u8>),
+    #[packetrs(id = "0x8028")]
+    Fingerprint { crc: u32 },
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(tag = "u8")]
+enum Command {
+    #[packetrs(id = "1")]
+    Ping,
+    #[packetrs(id = "2")]
+    Echo(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PacketrsRead)]
+#[packetrs(repr = "u8")]
+enum RtcpPacketType {
+    #[packetrs(id = "200")]
+    SenderReport,
+    Other(u8),
+}
+
+fn main() {
+    let attr = StunAttribute::from_bytes_with_ctx(&[0x61, 0x62], (0x0006, 2)).unwrap();
+    assert_eq!(attr.packetrs_id(), 0x0006);
+    assert_eq!(StunAttribute::PACKETRS_IDS, &[0x0006, 0x0008, 0x8028]);
+
+    let command = Command::from_bytes(&[0x02, 0x2a]).unwrap();
+    assert_eq!(command.packetrs_id(), 2);
+    assert_eq!(Command::PACKETRS_IDS, &[1, 2]);
+
+    assert_eq!(RtcpPacketType::SenderReport.packetrs_id(), 200);
+    assert_eq!(RtcpPacketType::Other(42).packetrs_id(), 42);
+    assert_eq!(RtcpPacketType::PACKETRS_IDS, &[200]);
+}
@@ -0,0 +1,68 @@
+use std::convert::TryFrom;
+
+use packetrs::error::UnknownEnumValue;
+use packetrs::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PacketrsRead)]
+#[packetrs(repr = "u2")]
+enum MessageClass {
+    Request = 0b00,
+    Indication = 0b01,
+    SuccessResponse = 0b10,
+    ErrorResponse = 0b11,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PacketrsRead)]
+#[packetrs(repr = "u8")]
+enum RtcpPacketType {
+    #[packetrs(id = "200")]
+    SenderReport,
+    #[packetrs(id = "201")]
+    ReceiverReport,
+    Other(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PacketrsRead)]
+#[packetrs(repr = "u4")]
+enum Version {
+    #[packetrs(id = "4")]
+    V4,
+    #[packetrs(id = "6")]
+    V6,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Header {
+    class: MessageClass,
+    version: Version,
+    reserved: u2,
+    packet_type: RtcpPacketType,
+}
+
+fn main() {
+    let header = Header::from_bytes(&[0b1001_1000, 200]).unwrap();
+    assert_eq!(header.class, MessageClass::SuccessResponse);
+    assert_eq!(header.version, Version::V6);
+    assert_eq!(header.packet_type, RtcpPacketType::SenderReport);
+
+    let header = Header::from_bytes(&[0b0001_0000, 42]).unwrap();
+    assert_eq!(header.class, MessageClass::Request);
+    assert_eq!(header.version, Version::V4);
+    assert_eq!(header.packet_type, RtcpPacketType::Other(42));
+
+    // An unknown value without a catch-all variant is an error
+    let error = Header::from_bytes(&[0b0011_1100, 200]).unwrap_err();
+    let unknown = error
+        .chain()
+        .find_map(|e| e.downcast_ref::<UnknownEnumValue>())
+        .unwrap();
+    assert_eq!(unknown.enum_name, "Version");
+    assert_eq!(unknown.value, 0xf);
+
+    assert_eq!(Version::try_from(u4::new(6)).unwrap(), Version::V6);
+    assert!(Version::try_from(u4::new(5)).is_err());
+    assert_eq!(u4::from(Version::V4), u4::new(4));
+    assert_eq!(u2::from(MessageClass::ErrorResponse), u2::new(0b11));
+    assert_eq!(u8::from(RtcpPacketType::ReceiverReport), 201);
+    assert_eq!(u8::from(RtcpPacketType::Other(42)), 42);
+}
@@ -0,0 +1,69 @@
+use packetrs::error::UnknownEnumValue;
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(tag = "u16")]
+enum Attribute {
+    #[packetrs(id = "0x0001")]
+    Username {
+        length: u16,
+        #[packetrs(count = "length")]
+        value: Vec<u8>,
+    },
+    #[packetrs(id = "0x8000..=0xffff")]
+    Unknown {
+        #[packetrs(read_value = "tag")]
+        attr_type: u16,
+        length: u16,
+        #[packetrs(count = "length")]
+        value: Vec<u8>,
+    },
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(
+    tag = "u4",
+    byte_order = "little_endian",
+    required_ctx = "version: u8",
+    key = "(version, u8::from(tag))"
+)]
+enum Command {
+    #[packetrs(id = "(1, 1)")]
+    Ping(u4),
+    #[packetrs(id = "(2, 1)")]
+    PingV2(u4, u16),
+}
+
+fn main() {
+    let attr = Attribute::from_bytes(&[0x00, 0x01, 0x00, 0x01, 0x61]).unwrap();
+    match attr {
+        Attribute::Username { ref value, .. } => assert_eq!(value, &vec![0x61]),
+        _ => panic!("Wrong attribute type: {:?}", attr),
+    }
+
+    let attr = Attribute::from_bytes(&[0x80, 0x22, 0x00, 0x00]).unwrap();
+    match attr {
+        Attribute::Unknown { attr_type, .. } => assert_eq!(attr_type, 0x8022),
+        _ => panic!("Wrong attribute type: {:?}", attr),
+    }
+
+    // A tag that doesn't match any variant is an error rather than a panic
+    let err = Attribute::from_bytes(&[0x00, 0x02, 0x00, 0x00]).unwrap_err();
+    let unknown = err
+        .chain()
+        .find_map(|e| e.downcast_ref::<UnknownEnumValue>())
+        .unwrap();
+    assert_eq!(unknown.enum_name, "Attribute");
+    assert_eq!(unknown.value, 0x0002);
+
+    let command = Command::from_bytes_with_ctx(&[0x12, 0x34, 0x12], (2,)).unwrap();
+    match command {
+        Command::PingV2(seq, value) => {
+            assert_eq!(seq, u4::new(2));
+            assert_eq!(value, 0x1234);
+        }
+        _ => panic!("Wrong command: {:?}", command),
+    }
+    let err = Command::from_bytes_with_ctx(&[0x12, 0x34, 0x12], (3,)).unwrap_err();
+    assert!(format!("{:#}", err).contains("(3, 1) is not a known Command value"));
+}
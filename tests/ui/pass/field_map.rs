@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use packetrs::prelude::*;
+// The prelude's anyhow glob brings in anyhow::Ok, which would shadow the std Ok used below
+use std::result::Result::Ok;
+
+#[derive(Debug, PartialEq)]
+enum Color {
+    Red,
+    Green,
+}
+
+impl TryFrom<u8> for Color {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Color::Red),
+            2 => Ok(Color::Green),
+            v => Err(format!("invalid color {}", v)),
+        }
+    }
+}
+
+#[derive(PacketrsRead)]
+struct MyStruct {
+    #[packetrs(read_as = "u16", map = "|v| Duration::from_millis(v as u64)")]
+    timeout: Duration,
+    #[packetrs(read_as = "u8", try_map = "Color::try_from")]
+    color: Color,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x01, 0xf4, 0x02];
+    let mut buf = BitCursor::from_vec(data);
+
+    let ms = MyStruct::read::<NetworkOrder>(&mut buf, ()).unwrap();
+    assert_eq!(ms.timeout, Duration::from_millis(500));
+    assert_eq!(ms.color, Color::Green);
+
+    let data: Vec<u8> = vec![0x01, 0xf4, 0x03];
+    let mut buf = BitCursor::from_vec(data);
+
+    let err = MyStruct::read::<NetworkOrder>(&mut buf, ()).err().unwrap();
+    let err_str = format!("{:#}", err);
+    assert!(err_str.contains("color"));
+    assert!(err_str.contains("invalid color 3"));
+}
@@ -0,0 +1,33 @@
+use packetrs::prelude::*;
+
+#[derive(PacketrsRead)]
+struct MyStruct {
+    length: u8,
+    #[packetrs(skip)]
+    received_at: Option<u64>,
+    #[packetrs(default = "length * 2")]
+    doubled: u8,
+    #[packetrs(when = "length > 10", default = "Some(0)")]
+    extra: Option<u8>,
+    #[packetrs(when = "length > 10", default = "42")]
+    other: u8,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x04];
+    let mut buf = BitCursor::from_vec(data);
+
+    let ms = MyStruct::read::<NetworkOrder>(&mut buf, ()).unwrap();
+    assert_eq!(ms.length, 4);
+    assert_eq!(ms.received_at, None);
+    assert_eq!(ms.doubled, 8);
+    assert_eq!(ms.extra, Some(0));
+    assert_eq!(ms.other, 42);
+
+    let data: Vec<u8> = vec![0x0b, 0x01, 0x02];
+    let mut buf = BitCursor::from_vec(data);
+
+    let ms = MyStruct::read::<NetworkOrder>(&mut buf, ()).unwrap();
+    assert_eq!(ms.extra, Some(1));
+    assert_eq!(ms.other, 2);
+}
@@ -0,0 +1,33 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Header {
+    length: u8,
+    flags: u8,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "length: u8")]
+struct Body {
+    #[packetrs(count = "length")]
+    values: Vec<u8>,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x02, 0x00, 0x0a, 0x0b];
+
+    assert!(Header::from_bytes(&data).is_err());
+    assert!(Header::from_bytes(&data[..2]).is_ok());
+
+    let (header, rest) = Header::from_bytes_partial(&data).unwrap();
+    assert_eq!(header.length, 2);
+    assert_eq!(header.flags, 0);
+    assert_eq!(rest, &[0x0a, 0x0b]);
+
+    let body = Body::from_bytes_with_ctx(rest, (header.length,)).unwrap();
+    assert_eq!(body.values, vec![0x0a, 0x0b]);
+
+    let (body, rest) = Body::from_bytes_partial_with_ctx(rest, (1,)).unwrap();
+    assert_eq!(body.values, vec![0x0a]);
+    assert_eq!(rest, &[0x0b]);
+}
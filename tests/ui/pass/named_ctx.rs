@@ -0,0 +1,42 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_type: u16, length: u16", key = "message_type")]
+enum Attribute {
+    #[packetrs(id = "0x0001", count = "length")]
+    Username(Vec<u8>),
+    #[packetrs(id = "0x0002", count = "length")]
+    Software(Vec<u8>),
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Tlv {
+    attr_type: u16,
+    attr_len: u16,
+    #[packetrs(ctx = "length = attr_len, message_type = attr_type")]
+    attr: Attribute,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct PositionalTlv {
+    attr_type: u16,
+    attr_len: u16,
+    #[packetrs(ctx = "attr_type, attr_len")]
+    attr: Attribute,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x00, 0x02, 0x00, 0x01, 0x0a];
+
+    let tlv = Tlv::from_bytes(&data).unwrap();
+    match tlv.attr {
+        Attribute::Software(ref v) => assert_eq!(v, &vec![0x0a]),
+        _ => panic!("Wrong attribute type: {:?}", tlv.attr),
+    }
+
+    let tlv = PositionalTlv::from_bytes(&data).unwrap();
+    match tlv.attr {
+        Attribute::Software(ref v) => assert_eq!(v, &vec![0x0a]),
+        _ => panic!("Wrong attribute type: {:?}", tlv.attr),
+    }
+}
@@ -0,0 +1,45 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Message {
+    pair: (u8, u16),
+    ports: [u16; 2],
+    num_strings: u8,
+    #[packetrs(count = "num_strings")]
+    lengths: Vec<u8>,
+    // Each string's length is the one at the same index in 'lengths'
+    #[packetrs(count = "num_strings", inner(count = "lengths[index]"))]
+    strings: Vec<Vec<u8>>,
+    has_extension: u8,
+    #[packetrs(when = "has_extension == 1", inner(count = "2u8"))]
+    extension: Option<Vec<u8>>,
+    flags: u8,
+    // Each bit of 'flags' denotes whether the corresponding value is present
+    #[packetrs(count = "8u8", inner(when = "flags & (1 << index) != 0"))]
+    values: Vec<Option<u8>>,
+    // A list of length-prefixed lists of pairs
+    num_lists: u8,
+    #[packetrs(count = "num_lists", inner(count = "buf.read_u8()?"))]
+    lists: Vec<Vec<(u8, u8)>>,
+}
+
+fn main() {
+    let pair: &[u8] = &[1, 0x00, 0x02];
+    let ports: &[u8] = &[0x00, 0x50, 0x01, 0xbb];
+    let strings: &[u8] = &[2, 2, 1, b'h', b'i', b'!'];
+    let extension: &[u8] = &[1, 0xaa, 0xbb];
+    let values: &[u8] = &[0b0000_0101, 10, 20];
+    let lists: &[u8] = &[2, 1, 1, 2, 0];
+    let data = [pair, ports, strings, extension, values, lists].concat();
+
+    let message = Message::from_bytes(&data).unwrap();
+    assert_eq!(message.pair, (1, 2));
+    assert_eq!(message.ports, [80, 443]);
+    assert_eq!(message.strings, vec![b"hi".to_vec(), b"!".to_vec()]);
+    assert_eq!(message.extension, Some(vec![0xaa, 0xbb]));
+    assert_eq!(
+        message.values,
+        vec![Some(10), None, Some(20), None, None, None, None, None]
+    );
+    assert_eq!(message.lists, vec![vec![(1, 2)], vec![]]);
+}
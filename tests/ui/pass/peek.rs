@@ -0,0 +1,75 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct RtpHeader {
+    version: u2,
+    padding: bool,
+    extension: bool,
+    csrc_count: u4,
+    marker: bool,
+    payload_type: u7,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct StunHeader {
+    msg_type: u16,
+    msg_length: u16,
+}
+
+/// Demultiplexing of packets received on the same port, based on their first byte (RFC 7983)
+#[derive(Debug, PacketrsRead)]
+#[packetrs(tag = "u8", peek)]
+enum Packet {
+    #[packetrs(id = "0..=3")]
+    Stun(StunHeader),
+    #[packetrs(id = "128..=191")]
+    Rtp(RtpHeader),
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Datagram {
+    // The version is only the first 2 bits, but is peeked into a u8
+    #[packetrs(peek_bits = "2")]
+    version: u8,
+    #[packetrs(peek)]
+    first_byte: u8,
+    packet: Packet,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct PeekedBits {
+    num_bits: u8,
+    #[packetrs(peek_bits = "num_bits")]
+    bits: u64,
+    value: [u8; 8],
+}
+
+fn main() {
+    let datagram = Datagram::from_bytes(&[0x80, 0x60]).unwrap();
+    assert_eq!(datagram.version, 2);
+    assert_eq!(datagram.first_byte, 0x80);
+    match datagram.packet {
+        Packet::Rtp(ref header) => assert_eq!(header.payload_type, u7::new(96)),
+        _ => panic!("Wrong packet type: {:?}", datagram.packet),
+    }
+
+    let datagram = Datagram::from_bytes(&[0x00, 0x01, 0x00, 0x00]).unwrap();
+    assert_eq!(datagram.version, 0);
+    match datagram.packet {
+        Packet::Stun(ref header) => assert_eq!(header.msg_type, 0x0001),
+        _ => panic!("Wrong packet type: {:?}", datagram.packet),
+    }
+
+    let data = [&[64u8][..], &[0xff; 8]].concat();
+    let peeked = PeekedBits::from_bytes(&data).unwrap();
+    assert_eq!(peeked.bits, u64::MAX);
+
+    // More bits than fit in the u64 they're collected in
+    let data = [&[65u8][..], &[0xff; 8]].concat();
+    let err = PeekedBits::from_bytes(&data).unwrap_err();
+    assert!(
+        format!("{:#}", err).contains("at most 64 bits"),
+        "{:#}",
+        err
+    );
+}
@@ -0,0 +1,78 @@
+use packetrs::error::LimitExceeded;
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Inner {
+    value: u8,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Outer {
+    length: u32,
+    #[packetrs(count = "length")]
+    values: Vec<u8>,
+    inner: Inner,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Unbounded {
+    #[packetrs(while = "true")]
+    values: Vec<u8>,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0xff, 0xff, 0xff, 0xff, 0x01, 0x02];
+    let mut buf = BitCursor::from_vec(data);
+    let mut opts = ReadOptions::new().max_collection_len(1024);
+
+    let err = Outer::read_with_options::<NetworkOrder>(&mut buf, (), &mut opts).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded::CollectionLength {
+            limit: 1024,
+            requested: 0xffff_ffff
+        })
+    );
+
+    let data: Vec<u8> = vec![0x00, 0x00, 0x00, 0x02, 0x01, 0x02, 0x03];
+    let mut buf = BitCursor::from_vec(data);
+    let mut opts = ReadOptions::new().max_depth(1);
+
+    let err = Outer::read_with_options::<NetworkOrder>(&mut buf, (), &mut opts).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded::Depth { limit: 1 })
+    );
+
+    let data: Vec<u8> = vec![0x00, 0x00, 0x00, 0x02, 0x01, 0x02, 0x03];
+    let mut buf = BitCursor::from_vec(data);
+    let mut opts = ReadOptions::new().max_depth(2).max_total_alloc(2);
+
+    assert!(Outer::read_with_options::<NetworkOrder>(&mut buf, (), &mut opts).is_ok());
+
+    // The limits apply to reads from byte slices, too
+    let data: &[u8] = &[0x00, 0x00, 0x00, 0x02, 0x01, 0x02, 0x03, 0x04];
+    let mut opts = ReadOptions::new().max_collection_len(1);
+    let err = Outer::from_bytes_with_options(&data[..7], &mut opts).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded::CollectionLength {
+            limit: 1,
+            requested: 2
+        })
+    );
+    let mut opts = ReadOptions::new().max_collection_len(2);
+    let (outer, rest) = Outer::from_bytes_partial_with_options(data, &mut opts).unwrap();
+    assert_eq!(outer.values, vec![0x01, 0x02]);
+    assert_eq!(rest, &[0x04]);
+
+    let data: Vec<u8> = vec![0x01, 0x02, 0x03];
+    let mut buf = BitCursor::from_vec(data);
+    let mut opts = ReadOptions::new().max_while_iterations(2);
+
+    let err = Unbounded::read_with_options::<NetworkOrder>(&mut buf, (), &mut opts).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded::WhileIterations { limit: 2 })
+    );
+}
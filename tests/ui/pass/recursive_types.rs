@@ -0,0 +1,60 @@
+use std::{rc::Rc, sync::Arc};
+
+use packetrs::error::LimitExceeded;
+use packetrs::prelude::*;
+
+// An expression tree, where each operator is followed by its operands
+#[derive(Debug, PacketrsRead)]
+#[packetrs(tag = "u8")]
+enum Expr {
+    #[packetrs(id = "0")]
+    Value { value: u8 },
+    #[packetrs(id = "1")]
+    Add { left: Box<Expr>, right: Box<Expr> },
+    #[packetrs(id = "2")]
+    Negate { operand: Box<Expr> },
+}
+
+impl Expr {
+    fn eval(&self) -> i32 {
+        match self {
+            Expr::Value { value } => *value as i32,
+            Expr::Add { left, right } => left.eval() + right.eval(),
+            Expr::Negate { operand } => -operand.eval(),
+        }
+    }
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "length: u8")]
+struct Payload {
+    #[packetrs(count = "length")]
+    data: Vec<u8>,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Shared {
+    length: u8,
+    #[packetrs(ctx = "length")]
+    payload: Rc<Payload>,
+    #[packetrs(ctx = "length = length")]
+    other_payload: Arc<Payload>,
+}
+
+fn main() {
+    // -(1 + (2 + 3))
+    let expr = Expr::from_bytes(&[2, 1, 0, 1, 1, 0, 2, 0, 3]).unwrap();
+    assert_eq!(expr.eval(), -6);
+
+    // Deeply nested input hits the default depth limit rather than overflowing the stack
+    let data = vec![2; 100_000];
+    let err = Expr::from_bytes(&data).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LimitExceeded>(),
+        Some(LimitExceeded::Depth { .. })
+    ));
+
+    let shared = Shared::from_bytes(&[2, 1, 2, 3, 4]).unwrap();
+    assert_eq!(shared.payload.data, vec![1, 2]);
+    assert_eq!(shared.other_payload.data, vec![3, 4]);
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use packetrs::packetrs_read::PacketrsNamedCtx;
+use packetrs::prelude::*;
+
+type ExtensionIdMap = HashMap<u8, String>;
+
+#[derive(Debug, Default)]
+struct DecodeStats {
+    elements_read: usize,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "id_map: &ExtensionIdMap, stats: &mut DecodeStats")]
+struct HeaderExtension {
+    id: u8,
+    len: u8,
+    #[packetrs(read_value = "id_map.get(&id).cloned()")]
+    name: Option<String>,
+    #[packetrs(count = "len")]
+    data: Vec<u8>,
+    #[packetrs(read_value = "{ stats.elements_read += 1; stats.elements_read }")]
+    index: usize,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "id_map: &ExtensionIdMap, stats: &mut DecodeStats")]
+struct HeaderExtensions {
+    num_extensions: u8,
+    // '&mut' context has to be explicitly reborrowed so it can be passed to each element
+    #[packetrs(count = "num_extensions", ctx = "id_map, &mut *stats")]
+    extensions: Vec<HeaderExtension>,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "extensions: &HeaderExtensions")]
+struct Payload {
+    #[packetrs(count = "extensions.extensions.len() as u32")]
+    data: Vec<u8>,
+}
+
+// The named context struct can only be named through the PacketrsNamedCtx impl
+type NamedPayloadCtx<'a> = <Payload as PacketrsNamedCtx<'a>>::Named;
+
+fn main() {
+    let mut id_map = HashMap::new();
+    id_map.insert(1, "urn:ietf:params:rtp-hdrext:ssrc-audio-level".to_owned());
+    let mut stats = DecodeStats::default();
+
+    let data: Vec<u8> = vec![0x02, 0x01, 0x01, 0xaa, 0x05, 0x00, 0x0b, 0x0c];
+    let mut buf = BitCursor::from_vec(data);
+    let extensions =
+        HeaderExtensions::read::<NetworkOrder>(&mut buf, (&id_map, &mut stats)).unwrap();
+    assert_eq!(extensions.extensions.len(), 2);
+    assert_eq!(
+        extensions.extensions[0].name.as_deref(),
+        Some("urn:ietf:params:rtp-hdrext:ssrc-audio-level")
+    );
+    assert_eq!(extensions.extensions[0].data, vec![0xaa]);
+    assert_eq!(extensions.extensions[1].name, None);
+    assert_eq!(extensions.extensions[1].index, 2);
+    assert_eq!(stats.elements_read, 2);
+
+    let payload = Payload::read::<NetworkOrder>(&mut buf, (&extensions,)).unwrap();
+    assert_eq!(payload.data, vec![0x0b, 0x0c]);
+
+    // Context with references can be passed by name, too
+    let payload = Payload::from_bytes_with_ctx(
+        &[0x01, 0x02],
+        <Payload as PacketrsNamedCtx<'_>>::into_ctx(NamedPayloadCtx {
+            extensions: &extensions,
+        }),
+    )
+    .unwrap();
+    assert_eq!(payload.data, vec![0x01, 0x02]);
+}
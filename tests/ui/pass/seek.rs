@@ -0,0 +1,58 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Entry {
+    // Relative to the start of the entry
+    name_offset: u8,
+    name_len: u8,
+    #[packetrs(at = "name_offset", count = "name_len")]
+    name: Vec<u8>,
+    value: u8,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Table {
+    num_entries: u8,
+    #[packetrs(count = "num_entries")]
+    entries: Vec<Entry>,
+    // The names are read again, and this time the cursor is left after them
+    #[packetrs(
+        at = "1 + 3 * num_entries",
+        seek_from_start,
+        seek_mode = "advance",
+        count = "3"
+    )]
+    names: Vec<u8>,
+}
+
+fn skip_version(buf: &mut BitCursor) -> PacketRsResult<()> {
+    buf.read_u8()?;
+    Ok(())
+}
+
+// The offset is a ux value, and is relative to the start of the record: before its version, which
+// the 'pre_read' hook skips
+#[derive(Debug, PacketrsRead)]
+#[packetrs(pre_read = "skip_version")]
+struct Record {
+    flags: u4,
+    offset: u12,
+    #[packetrs(at = "offset")]
+    value: u8,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x02, 0x06, 0x02, 0x11, 0x05, 0x01, 0x22, b'a', b'b', b'c'];
+    let table = Table::from_bytes(&data).unwrap();
+    assert_eq!(table.entries.len(), 2);
+    assert_eq!(table.entries[0].name, b"ab".to_vec());
+    assert_eq!(table.entries[0].value, 0x11);
+    assert_eq!(table.entries[1].name, b"c".to_vec());
+    assert_eq!(table.entries[1].value, 0x22);
+    assert_eq!(table.names, b"abc".to_vec());
+
+    let (record, rest) = Record::from_bytes_partial(&[0x01, 0x00, 0x04, 0xaa, 0xbb]).unwrap();
+    assert_eq!(record.offset, u12::new(4));
+    assert_eq!(record.value, 0xbb);
+    assert_eq!(rest, &[0xaa, 0xbb]);
+}
@@ -0,0 +1,58 @@
+use std::borrow::Cow;
+
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct Username {
+    length: u16,
+    #[packetrs(string = "utf8", byte_len = "length")]
+    username: String,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Message {
+    username: Username,
+    #[packetrs(string = "ascii", cstr)]
+    header_name: String,
+    #[packetrs(string = "latin1", len_prefix = "u8")]
+    header_value: Cow<'static, str>,
+    num_tokens: u8,
+    #[packetrs(count = "num_tokens", inner(string = "ascii", len_prefix = "u8"))]
+    tokens: Vec<String>,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Realm {
+    has_realm: u8,
+    // The 'string' params apply to the Option's value, which is only read when there is one
+    #[packetrs(when = "has_realm != 0", string = "utf8", cstr)]
+    realm: Option<String>,
+    #[packetrs(count = "2", string = "ascii", len_prefix = "u8")]
+    nonces: Vec<String>,
+}
+
+fn main() {
+    let username: &[u8] = &[0x00, 0x06, b'j', 0xc3, 0xa9, b'r', b'o', b'm'];
+    let header: &[u8] = &[b'H', b'o', b's', b't', 0, 3, b'c', 0xe9, b'z'];
+    let tokens: &[u8] = &[2, 1, b'a', 2, b'b', b'c'];
+    let data = [username, header, tokens].concat();
+
+    let message = Message::from_bytes(&data).unwrap();
+    assert_eq!(message.username.username, "jérom");
+    assert_eq!(message.header_name, "Host");
+    assert_eq!(message.header_value, "céz");
+    assert_eq!(message.tokens, vec!["a", "bc"]);
+
+    let realm = Realm::from_bytes(&[1, b'e', b'x', 0, 1, b'a', 0]).unwrap();
+    assert_eq!(realm.realm.as_deref(), Some("ex"));
+    assert_eq!(realm.nonces, vec!["a", ""]);
+    let realm = Realm::from_bytes(&[0, 1, b'a', 1, b'b']).unwrap();
+    assert_eq!(realm.realm, None);
+    assert_eq!(realm.nonces, vec!["a", "b"]);
+
+    // Invalid UTF-8 is reported along with the path of the field
+    let err = Message::from_bytes(&[0x00, 0x01, 0xff]).unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("username: username"), "{}", message);
+    assert!(message.contains("Invalid UTF-8"), "{}", message);
+}
@@ -0,0 +1,37 @@
+use packetrs::prelude::*;
+
+#[packetrs_derive(PacketrsRead)]
+#[derive(Debug, PartialEq)]
+struct MyStruct {
+    #[packetrs(temp)]
+    length: u8,
+    #[packetrs(count = "length")]
+    values: Vec<u8>,
+}
+
+#[packetrs_derive(PacketrsRead)]
+#[derive(Debug, PartialEq)]
+#[packetrs(required_ctx = "ty: u8", key = "ty")]
+enum MyEnum {
+    #[packetrs(id = "1")]
+    One {
+        #[packetrs(temp)]
+        length: u8,
+        #[packetrs(count = "length")]
+        values: Vec<u8>,
+    },
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x02, 0x0a, 0x0b];
+    let mut buf = BitCursor::from_vec(data);
+
+    let ms = MyStruct::read::<NetworkOrder>(&mut buf, ()).unwrap();
+    assert_eq!(ms, MyStruct { values: vec![0x0a, 0x0b] });
+
+    let data: Vec<u8> = vec![0x01, 0x0a];
+    let mut buf = BitCursor::from_vec(data);
+
+    let me = MyEnum::read::<NetworkOrder>(&mut buf, (1,)).unwrap();
+    assert_eq!(me, MyEnum::One { values: vec![0x0a] });
+}
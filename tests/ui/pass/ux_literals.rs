@@ -0,0 +1,65 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(required_ctx = "message_type: u14", key = "message_type")]
+enum MessageBody {
+    #[packetrs(id = "0x0001")]
+    BindingRequest,
+    #[packetrs(id = "0x0101")]
+    BindingResponse,
+    #[packetrs(id = "0x0200..=0x02ff")]
+    Other,
+}
+
+#[derive(Debug, PacketrsRead)]
+#[packetrs(tag = "u4")]
+enum Flag {
+    #[packetrs(id = "0b0001")]
+    One,
+    #[packetrs(id = "0b0010")]
+    Two,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct StunHeader {
+    #[packetrs(fixed = "0")]
+    reserved: u2,
+    #[packetrs(assert = "|t| t < 0x1000")]
+    msg_type: u14,
+    msg_length: u16,
+    #[packetrs(fixed = "0x2112A442")]
+    cookie: u32,
+    #[packetrs(ctx = "msg_type")]
+    body: MessageBody,
+    flag: Flag,
+    #[packetrs(fixed = "0b0000")]
+    padding: u4,
+}
+
+// The assert closure is passed the field's value, so it can also be written against the ux type
+#[derive(Debug, PacketrsRead)]
+struct MessageType {
+    #[packetrs(fixed = "0")]
+    reserved: u2,
+    #[packetrs(assert = "|v: u14| v < u14::new(0x1000)")]
+    msg_type: u14,
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0x01, 0x01, 0x00, 0x00, 0x21, 0x12, 0xA4, 0x42, 0x20];
+    let header = StunHeader::from_bytes(&data).unwrap();
+    assert_eq!(header.msg_type, u14::new(0x0101));
+    assert!(matches!(header.body, MessageBody::BindingResponse));
+    assert!(matches!(header.flag, Flag::Two));
+
+    // The fixed reserved bits aren't zero
+    let data: Vec<u8> = vec![0x41, 0x01, 0x00, 0x00, 0x21, 0x12, 0xA4, 0x42, 0x20];
+    assert!(StunHeader::from_bytes(&data).is_err());
+
+    // The message type fails the assertion
+    let data: Vec<u8> = vec![0x10, 0x01, 0x00, 0x00, 0x21, 0x12, 0xA4, 0x42, 0x20];
+    assert!(StunHeader::from_bytes(&data).is_err());
+
+    assert!(MessageType::from_bytes(&[0x01, 0x01]).is_ok());
+    assert!(MessageType::from_bytes(&[0x10, 0x01]).is_err());
+}
@@ -0,0 +1,73 @@
+use packetrs::prelude::*;
+
+// Validation errors don't have to be anyhow::Errors, just displayable
+fn validate_padding(value: &MyStruct) -> Result<(), String> {
+    (value.padding_len as usize <= value.payload.len())
+        .then_some(())
+        .ok_or_else(|| {
+            format!(
+                "padding length {} exceeds payload length {}",
+                value.padding_len,
+                value.payload.len()
+            )
+        })
+}
+
+fn skip_marker(buf: &mut BitCursor) -> PacketRsResult<()> {
+    buf.read_u8()?;
+    Ok(())
+}
+
+fn remove_padding(value: &mut MyStruct, _buf: &mut BitCursor) -> PacketRsResult<()> {
+    let payload_len = value
+        .payload
+        .len()
+        .saturating_sub(value.padding_len as usize);
+    value.payload.truncate(payload_len);
+    Ok(())
+}
+
+#[derive(PacketrsRead)]
+#[packetrs(
+    pre_read = "skip_marker",
+    post_read = "remove_padding",
+    validate = "validate_padding"
+)]
+struct MyStruct {
+    padding_len: u8,
+    #[packetrs(count = "3")]
+    payload: Vec<u8>,
+}
+
+fn validate_enum(value: &MyEnum) -> PacketRsResult<()> {
+    match value {
+        MyEnum::One(v) if *v > 10 => bail!("value too large"),
+        _ => Ok(()),
+    }
+}
+
+#[derive(PacketrsRead)]
+#[packetrs(required_ctx = "ty: u8", key = "ty", validate = "validate_enum")]
+enum MyEnum {
+    #[packetrs(id = "1")]
+    One(u8),
+}
+
+fn main() {
+    let data: Vec<u8> = vec![0xff, 0x01, 0x0a, 0x0b, 0x00];
+    let mut buf = BitCursor::from_vec(data);
+
+    let ms = MyStruct::read::<NetworkOrder>(&mut buf, ()).unwrap();
+    assert_eq!(ms.payload, vec![0x0a, 0x0b]);
+
+    let data: Vec<u8> = vec![0xff, 0x04, 0x0a, 0x0b, 0x00];
+    let mut buf = BitCursor::from_vec(data);
+
+    let err = MyStruct::read::<NetworkOrder>(&mut buf, ()).err().unwrap();
+    assert!(format!("{:#}", err).contains("MyStruct failed validation"));
+
+    let data: Vec<u8> = vec![0x0b];
+    let mut buf = BitCursor::from_vec(data);
+
+    assert!(MyEnum::read::<NetworkOrder>(&mut buf, (1,)).is_err());
+}
@@ -0,0 +1,46 @@
+use packetrs::prelude::*;
+
+#[derive(Debug, PacketrsRead)]
+struct AckFrame {
+    #[packetrs(encoding = "quic_varint")]
+    largest_acknowledged: u64,
+    #[packetrs(encoding = "quic_varint")]
+    ack_range_count: u64,
+    // The varint count drives the collection, and each element is a varint too
+    #[packetrs(count = "ack_range_count", inner(encoding = "quic_varint"))]
+    ack_ranges: Vec<u64>,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Telemetry {
+    #[packetrs(encoding = "uleb128")]
+    length: u32,
+    #[packetrs(count = "length")]
+    payload: Vec<u8>,
+    #[packetrs(encoding = "sleb128")]
+    offset: i64,
+    #[packetrs(encoding = "zigzag")]
+    delta: i32,
+}
+
+#[derive(Debug, PacketrsRead)]
+struct Small {
+    #[packetrs(encoding = "uleb128")]
+    value: u8,
+}
+
+fn main() {
+    let frame = AckFrame::from_bytes(&[0x7b, 0xbd, 0x02, 0x01, 0x40, 0x25]).unwrap();
+    assert_eq!(frame.largest_acknowledged, 15_293);
+    assert_eq!(frame.ack_ranges, vec![1, 37]);
+
+    let telemetry = Telemetry::from_bytes(&[0x02, 0xaa, 0xbb, 0x7e, 0x03]).unwrap();
+    assert_eq!(telemetry.payload, vec![0xaa, 0xbb]);
+    assert_eq!(telemetry.offset, -2);
+    assert_eq!(telemetry.delta, -2);
+
+    // The decoded value must fit in the field's type
+    assert!(Small::from_bytes(&[0x80, 0x02]).is_err());
+    // Over-long encodings are rejected
+    assert!(Small::from_bytes(&[0x81, 0x00]).is_err());
+}